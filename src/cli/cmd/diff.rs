@@ -0,0 +1,705 @@
+// Library
+use clap::Parser;
+use hex_ray::utils::{
+    ansi::{self, Color, Colorable},
+    format::{Format, Radix},
+    helpers, source,
+};
+use hex_ray::Dumper;
+use std::io::Write;
+
+// ------------
+// DIFF COMMAND
+// ------------
+
+/// The maximum number of inserted and deleted bytes to align the files around.
+/// Beyond this, the remaining bytes are compared position by position.
+const MAX_EDITS: usize = 2000;
+
+/// The maximum number of differing bytes (in both files together) to align. The search for the
+/// alignment takes time in proportion to them, so larger differences are compared position by
+/// position too.
+const MAX_ALIGN_BYTES: usize = 4 * 1024 * 1024;
+
+#[derive(Parser, Clone)]
+#[command(version, about)]
+pub struct Diff {
+    /// Path to the original file
+    pub left: std::path::PathBuf,
+
+    /// Path to the modified file
+    pub right: std::path::PathBuf,
+
+    /// The size of each row (for each file)
+    #[arg(short, long, value_parser = crate::cli::parse_count, default_value_t = 8)]
+    pub size: usize,
+
+    /// The output display format.
+    ///
+    /// This can be any of the formats supported by `view`.
+    #[arg(short, long, default_value = "hex")]
+    pub format: Format,
+
+    /// Chunk the output into groups of this size
+    #[arg(alias = "chunk", short, long, value_parser = crate::cli::parse_count, default_value_t = 4)]
+    pub group_size: usize,
+
+    /// The radix of the offset column: hex (x), decimal (d) or octal (o)
+    #[arg(short, long, value_enum, default_value_t)]
+    pub radix: Radix,
+
+    /// Collapse the rows without differences, leaving only the rows around the changes
+    #[arg(short, long)]
+    pub collapse: bool,
+
+    /// The number of unchanged rows to show around the changes when collapsing
+    #[arg(long, default_value_t = 1)]
+    pub context: usize,
+
+    /// Output the differing ranges as JSON instead of the table
+    #[arg(long)]
+    pub json: bool,
+
+    /// Disable ANSI colors
+    #[arg(short, long)]
+    pub no_color: bool,
+
+    /// Simple Output
+    #[arg(alias = "plain", short = 'p', long)]
+    pub simple: bool,
+}
+
+/// A byte from each file at the same position in the alignment.
+/// `None` marks a gap, where the byte was deleted from (or inserted into) the other file.
+type Pair = (Option<u8>, Option<u8>);
+
+/// A range of bytes that differs between the two files
+#[derive(Debug, PartialEq)]
+struct Range {
+    left: (usize, usize),  // The offset and length of the range in the left file
+    right: (usize, usize), // The offset and length of the range in the right file
+}
+
+impl Diff {
+    pub fn execute(self) -> hex_ray::Result<()> {
+        // Disable ANSI colors by setting the `NO_COLOR` env variable
+        if self.no_color || self.simple {
+            std::env::set_var("NO_COLOR", "true");
+        }
+
+        // Regular files are memory-mapped, rather than read into memory
+        let mut left = source::open(Some(&self.left), 0, None)?;
+        let mut right = source::open(Some(&self.right), 0, None)?;
+        let (left_len, right_len) = (left.len(), right.len());
+        let left = left.bytes(0..left_len)?;
+        let right = right.bytes(0..right_len)?;
+        let alignment = Alignment::new(&left, &right);
+        let ranges = alignment.differing_ranges();
+
+        let mut writer = std::io::BufWriter::new(std::io::stdout().lock());
+        if self.json {
+            self.print_json(&mut writer, &ranges)?;
+        } else {
+            let max_offset = std::cmp::max(left.len(), right.len());
+            self.print_table(&mut writer, &alignment, max_offset)?;
+            self.print_summary(&mut writer, &ranges, alignment.is_aligned(), max_offset)?;
+        }
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    /// The dumper used to lay out each side of the table, with the offset column
+    /// wide enough to fit the given offset
+    fn dumper(&self, max_offset: usize) -> Dumper {
+        Dumper::new()
+            .size(self.size)
+            .group_size(self.group_size)
+            .format(self.format.clone())
+            .radix(self.radix)
+            .offset_width(self.radix.width(max_offset as u64))
+            .color(ansi::is_color_enabled())
+            .plain(self.simple)
+    }
+
+    /// Print both files side by side, with a marker between the rows that differ.
+    ///
+    /// The rows are laid out one at a time, and the pairs of the rows in the common prefix and
+    /// suffix are only put together if they are shown
+    fn print_table<W: Write>(
+        &self,
+        writer: &mut W,
+        alignment: &Alignment,
+        max_offset: usize,
+    ) -> std::io::Result<()> {
+        let dumper = self.dumper(max_offset);
+        let len = alignment.len();
+        let rows = len.div_ceil(self.size);
+        let row_range = |i: usize| i * self.size..std::cmp::min((i + 1) * self.size, len);
+
+        // Only the rows that overlap the middle of the alignment can differ
+        let middle = alignment.middle();
+        let overlaps_middle = |i: usize| {
+            let range = row_range(i);
+            range.start < middle.end && middle.start < range.end
+        };
+        let is_changed = |pairs: &[Pair]| pairs.iter().any(|(l, r)| l != r);
+        let is_row_changed =
+            |i: usize| overlaps_middle(i) && is_changed(&alignment.pairs(row_range(i)));
+
+        // Determine which rows to show when collapsing unchanged rows
+        let middle_rows = middle.start / self.size..middle.end.div_ceil(self.size);
+        let is_visible = |i: usize| {
+            if !self.collapse {
+                return true;
+            }
+            if i + self.context < middle_rows.start || i >= middle_rows.end + self.context {
+                return false;
+            }
+            let start = i.saturating_sub(self.context);
+            let end = std::cmp::min(i + self.context + 1, rows);
+            (start..end).any(is_row_changed)
+        };
+
+        if !self.simple {
+            writeln!(
+                writer,
+                "Source: {} → {}",
+                self.left.to_string_lossy(),
+                self.right.to_string_lossy()
+            )?;
+            let top = dumper.format_border("┌─", "┬─", "─┬─", "─┐");
+            writeln!(writer, "{}   {}", top, top)?;
+        }
+
+        // The offsets of the first byte of the current row in each file
+        let (mut left_offset, mut right_offset) = (0, 0);
+        let mut hidden = 0;
+
+        for i in 0..rows {
+            let show = is_visible(i);
+            if !show && !overlaps_middle(i) {
+                // Both files have every byte of the rows outside the middle
+                hidden += 1;
+                left_offset += row_range(i).len();
+                right_offset += row_range(i).len();
+                continue;
+            }
+
+            let row = alignment.pairs(row_range(i));
+            if show {
+                if hidden > 0 {
+                    self.print_collapsed(writer, hidden)?;
+                    hidden = 0;
+                }
+                let marker = if is_changed(&row) { "≠" } else { " " };
+                writeln!(
+                    writer,
+                    "{} {} {}",
+                    self.format_side(&dumper, &row, left_offset, |(l, _)| *l, Color::Red),
+                    marker.ansi(Color::White),
+                    self.format_side(&dumper, &row, right_offset, |(_, r)| *r, Color::Green),
+                )?;
+            } else {
+                hidden += 1;
+            }
+            left_offset += row.iter().filter(|(l, _)| l.is_some()).count();
+            right_offset += row.iter().filter(|(_, r)| r.is_some()).count();
+        }
+        if hidden > 0 {
+            self.print_collapsed(writer, hidden)?;
+        }
+
+        if !self.simple {
+            let bottom = dumper.format_border("└─", "┴─", "─┴─", "─┘");
+            writeln!(writer, "{}   {}", bottom, bottom)?;
+        }
+        Ok(())
+    }
+
+    /// Print the marker line in place of the collapsed rows
+    fn print_collapsed<W: Write>(&self, writer: &mut W, n: usize) -> std::io::Result<()> {
+        let line = format!("⋮ {} identical row(s)", n);
+        writeln!(writer, "{}", line.ansi(Color::Black))
+    }
+
+    /// Format one side of a row of the table, highlighting the bytes that differ
+    fn format_side(
+        &self,
+        dumper: &Dumper,
+        row: &[Pair],
+        offset: usize,
+        side: impl Fn(&Pair) -> Option<u8>,
+        color: Color,
+    ) -> String {
+        let is_changed = |pairs: &[Pair]| pairs.iter().any(|(l, r)| l != r);
+        let width = self.format.size();
+
+        // Hex Values
+        let mut hex_values = String::new();
+        for (j, pairs) in row.chunks(self.format.bytes()).enumerate() {
+            if dumper.is_group_start(j) {
+                hex_values.push(' ');
+            }
+            let bytes: Vec<u8> = pairs.iter().filter_map(&side).collect();
+            let value = if bytes.is_empty() {
+                "-".repeat(width).ansi(Color::Black) // A gap in this file
+            } else if is_changed(pairs) {
+                self.format.format_bytes(&bytes).ansi(color)
+            } else {
+                self.format.format_bytes(&bytes).ansi(Color::White)
+            };
+            hex_values.push_str(&value);
+            hex_values.push(' ');
+        }
+        for k in row.len().div_ceil(self.format.bytes())..dumper.values_per_row() {
+            if dumper.is_group_start(k) {
+                hex_values.push(' ');
+            }
+            hex_values.push_str(&" ".repeat(width + 1));
+        }
+
+        // ASCII Values
+        let mut ascii_values = String::new();
+        for k in 0..self.size {
+            if k > 0 && k % self.group_size == 0 {
+                ascii_values.push(' ');
+            }
+            let Some(pair) = row.get(k) else {
+                ascii_values.push(' ');
+                continue;
+            };
+            let c = match side(pair) {
+                None => "-".ansi(Color::Black),
+                Some(byte) if helpers::is_printable_ascii_character(&byte) => {
+                    let color = if pair.0 != pair.1 {
+                        color
+                    } else {
+                        Color::White
+                    };
+                    (byte as char).to_string().ansi(color)
+                }
+                Some(_) => "·".ansi(Color::Black),
+            };
+            ascii_values.push_str(&c);
+        }
+
+        // Leave the offset blank if the row is entirely a gap in this file
+        let offset = if row.iter().any(|p| side(p).is_some()) {
+            dumper.format_offset(offset)
+        } else {
+            dumper.format_blank_offset()
+        };
+        dumper.format_row(&offset, &hex_values, &ascii_values)
+    }
+
+    /// Print the summary of the differing ranges, with the offsets in the radix of the offset
+    /// column and wide enough to fit the given offset
+    fn print_summary<W: Write>(
+        &self,
+        writer: &mut W,
+        ranges: &[Range],
+        aligned: bool,
+        max_offset: usize,
+    ) -> std::io::Result<()> {
+        if ranges.is_empty() {
+            return writeln!(writer, "The files are identical");
+        }
+
+        let bytes: usize = ranges
+            .iter()
+            .map(|r| std::cmp::max(r.left.1, r.right.1))
+            .sum();
+        writeln!(
+            writer,
+            "{} differing range(s), {} byte(s) in total",
+            ranges.len(),
+            bytes
+        )?;
+        if !aligned {
+            writeln!(
+                writer,
+                "(too many differences to align around; the rest was compared byte by byte)"
+            )?;
+        }
+        let width = self.radix.width(max_offset as u64);
+        let offset = |offset: usize| format!("{:0>1$}", self.radix.format(offset as u64), width);
+        for range in ranges {
+            writeln!(
+                writer,
+                "  {}..{} ({} bytes) → {}..{} ({} bytes)",
+                offset(range.left.0),
+                offset(range.left.0 + range.left.1),
+                range.left.1,
+                offset(range.right.0),
+                offset(range.right.0 + range.right.1),
+                range.right.1,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Print the differing ranges as a JSON array
+    fn print_json<W: Write>(&self, writer: &mut W, ranges: &[Range]) -> std::io::Result<()> {
+        let ranges: Vec<String> = ranges
+            .iter()
+            .map(|r| {
+                format!(
+                    r#"{{"left_offset":{},"left_length":{},"right_offset":{},"right_length":{}}}"#,
+                    r.left.0, r.left.1, r.right.0, r.right.1
+                )
+            })
+            .collect();
+        writeln!(writer, "[{}]", ranges.join(","))
+    }
+}
+
+/// The bytes of the two files lined up against each other, such that inserted and deleted bytes
+/// show up as gaps.
+///
+/// Only the middle, between the common prefix and suffix, is aligned and held as pairs. The
+/// prefix and suffix are read from the files as they are needed.
+struct Alignment<'a> {
+    left: &'a [u8],            // The bytes of the original file
+    right: &'a [u8],           // The bytes of the modified file
+    prefix: usize,             // The length of the common prefix
+    suffix: usize,             // The length of the common suffix
+    middle: Option<Vec<Pair>>, // The aligned pairs of the middle, or `None` if it is compared position by position
+}
+
+impl<'a> Alignment<'a> {
+    /// Align the two files
+    fn new(left: &'a [u8], right: &'a [u8]) -> Self {
+        // Set aside the common prefix and suffix, as these do not need to be aligned
+        let prefix = left.iter().zip(right).take_while(|(l, r)| l == r).count();
+        let suffix = left[prefix..]
+            .iter()
+            .rev()
+            .zip(right[prefix..].iter().rev())
+            .take_while(|(l, r)| l == r)
+            .count();
+        let (a, b) = (
+            &left[prefix..left.len() - suffix],
+            &right[prefix..right.len() - suffix],
+        );
+
+        let middle = match a.len() + b.len() <= MAX_ALIGN_BYTES {
+            true => edit_script(a, b),
+            false => None,
+        };
+        Self {
+            left,
+            right,
+            prefix,
+            suffix,
+            middle,
+        }
+    }
+
+    /// Returns true if the alignment succeeded (as opposed to falling back to comparing the
+    /// bytes of the middle position by position)
+    fn is_aligned(&self) -> bool {
+        self.middle.is_some()
+    }
+
+    /// The range of the positions of the middle, the only part where the files can differ
+    fn middle(&self) -> std::ops::Range<usize> {
+        let len = match &self.middle {
+            Some(pairs) => pairs.len(),
+            None => std::cmp::max(self.left.len(), self.right.len()) - self.prefix - self.suffix,
+        };
+        self.prefix..self.prefix + len
+    }
+
+    /// The number of positions in the alignment
+    fn len(&self) -> usize {
+        self.middle().end + self.suffix
+    }
+
+    /// Returns the pair at the given position
+    fn pair(&self, i: usize) -> Pair {
+        let middle = self.middle();
+        if i < middle.start {
+            return (Some(self.left[i]), Some(self.right[i]));
+        }
+        if i >= middle.end {
+            let k = i - middle.end;
+            let (l, r) = (self.left.len(), self.right.len());
+            return (
+                Some(self.left[l - self.suffix + k]),
+                Some(self.right[r - self.suffix + k]),
+            );
+        }
+        let j = i - middle.start;
+        match &self.middle {
+            Some(pairs) => pairs[j],
+            None => {
+                let side = |bytes: &[u8]| {
+                    let end = bytes.len() - self.suffix;
+                    bytes[self.prefix..end].get(j).copied()
+                };
+                (side(self.left), side(self.right))
+            }
+        }
+    }
+
+    /// Returns the pairs at the given range of positions
+    fn pairs(&self, range: std::ops::Range<usize>) -> Vec<Pair> {
+        range.map(|i| self.pair(i)).collect()
+    }
+
+    /// Collects the ranges of bytes that differ, all of which lie in the middle
+    fn differing_ranges(&self) -> Vec<Range> {
+        let pairs = self.middle().map(|i| self.pair(i));
+        differing_ranges(pairs, self.prefix)
+    }
+}
+
+/// Computes the shortest edit script that turns `a` into `b` using Myers' algorithm.
+///
+/// Runs of deletions followed by insertions are paired up as substitutions.
+/// Returns `None` if more than [`MAX_EDITS`] insertions and deletions are needed.
+fn edit_script(a: &[u8], b: &[u8]) -> Option<Vec<Pair>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+
+    // `v[k]` is the furthest `x` reached on the diagonal `k = x - y`. The trace records
+    // `v` for diagonals `-(d + 1)..=(d + 1)` before each step `d`, for backtracking.
+    let mut v = vec![0isize; 2 * MAX_EDITS + 3];
+    let mid = MAX_EDITS as isize + 1;
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    let mut steps = None;
+    'search: for d in 0..=(MAX_EDITS as isize) {
+        trace.push(v[(mid - d - 1) as usize..=(mid + d + 1) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let i = (mid + k) as usize;
+            let mut x = if k == -d || (k != d && v[i - 1] < v[i + 1]) {
+                v[i + 1] // Move down (insertion)
+            } else {
+                v[i - 1] + 1 // Move right (deletion)
+            };
+            let mut y = x - k;
+            // Follow the diagonal for as long as the bytes match
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[i] = x;
+            if x >= n && y >= m {
+                steps = Some(d);
+                break 'search;
+            }
+        }
+    }
+    let steps = steps?;
+
+    // Backtrack through the trace to recover the path
+    let mut ops = Vec::new(); // In reverse; `Some(true)` for deletions, `Some(false)` for insertions
+    let (mut x, mut y) = (n, m);
+    for d in (0..=steps).rev() {
+        let v = &trace[d as usize];
+        let at = |k: isize| v[(k + d + 1) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = at(prev_k);
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            ops.push(None);
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            ops.push(Some(x != prev_x));
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    ops.reverse();
+
+    // Walk the edit script, pairing up deletions and insertions
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    let mut ops = ops.into_iter().peekable();
+    while let Some(op) = ops.next() {
+        if op.is_none() {
+            pairs.push((Some(a[i]), Some(b[j])));
+            i += 1;
+            j += 1;
+            continue;
+        }
+
+        // Count the run of changes
+        let (mut deleted, mut inserted) = (0, 0);
+        let mut op = op;
+        loop {
+            match op {
+                Some(true) => deleted += 1,
+                _ => inserted += 1,
+            }
+            match ops.peek() {
+                Some(Some(_)) => op = ops.next().unwrap(),
+                _ => break,
+            }
+        }
+
+        for k in 0..std::cmp::max(deleted, inserted) {
+            let l = (k < deleted).then(|| a[i + k]);
+            let r = (k < inserted).then(|| b[j + k]);
+            pairs.push((l, r));
+        }
+        i += deleted;
+        j += inserted;
+    }
+
+    Some(pairs)
+}
+
+/// Collects the contiguous runs of differing pairs into ranges of offsets in each file, where the
+/// first pair is at the `offset` of both files
+fn differing_ranges(pairs: impl IntoIterator<Item = Pair>, offset: usize) -> Vec<Range> {
+    let mut ranges = Vec::new();
+    let (mut left, mut right) = (offset, offset);
+    let mut current: Option<Range> = None;
+
+    for (l, r) in pairs {
+        if l != r {
+            let range = current.get_or_insert(Range {
+                left: (left, 0),
+                right: (right, 0),
+            });
+            range.left.1 += l.is_some() as usize;
+            range.right.1 += r.is_some() as usize;
+        } else if let Some(range) = current.take() {
+            ranges.push(range);
+        }
+        left += l.is_some() as usize;
+        right += r.is_some() as usize;
+    }
+    ranges.extend(current);
+
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Aligns the two files, returning all the pairs and whether the alignment succeeded
+    fn align(left: &[u8], right: &[u8]) -> (Vec<Pair>, bool) {
+        let alignment = Alignment::new(left, right);
+        (alignment.pairs(0..alignment.len()), alignment.is_aligned())
+    }
+
+    /// Renders the aligned pairs as two strings, using `-` for the gaps
+    fn render(pairs: &[Pair]) -> (String, String) {
+        let side = |f: fn(&Pair) -> Option<u8>| {
+            pairs
+                .iter()
+                .map(|p| f(p).map_or('-', |b| b as char))
+                .collect()
+        };
+        (side(|p| p.0), side(|p| p.1))
+    }
+
+    #[test]
+    fn should_align_around_insertions() {
+        let (pairs, aligned) = align(b"abcdef", b"abXXcdef");
+        assert!(aligned);
+        assert_eq!(
+            render(&pairs),
+            (String::from("ab--cdef"), String::from("abXXcdef"))
+        );
+    }
+
+    #[test]
+    fn should_align_around_deletions_and_substitutions() {
+        let (pairs, _) = align(b"hello world", b"jello word");
+        assert_eq!(
+            render(&pairs),
+            (String::from("hello world"), String::from("jello wor-d"))
+        );
+    }
+
+    #[test]
+    fn should_collect_differing_ranges() {
+        assert_eq!(
+            Alignment::new(b"abcdef", b"abXXcdeY").differing_ranges(),
+            vec![
+                Range {
+                    left: (2, 0),
+                    right: (2, 2)
+                },
+                Range {
+                    left: (5, 1),
+                    right: (7, 1)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_fall_back_to_comparing_by_position() {
+        // Unrelated pseudo-random bytes need far more than `MAX_EDITS` edits to align
+        let noise = |mut state: u32| {
+            (0..MAX_EDITS * 2)
+                .map(|_| {
+                    state ^= state << 13;
+                    state ^= state >> 17;
+                    state ^= state << 5;
+                    state as u8
+                })
+                .collect::<Vec<u8>>()
+        };
+        let (a, b) = (noise(1), noise(2));
+        let (pairs, aligned) = align(&a, &b);
+        assert!(!aligned);
+        assert_eq!(pairs.len(), a.len());
+
+        // Differences spanning too many bytes are not aligned either, however few the edits
+        let a = [vec![0; MAX_ALIGN_BYTES / 2], vec![1]].concat();
+        let b = [vec![2], vec![0; MAX_ALIGN_BYTES / 2]].concat();
+        let (pairs, aligned) = align(&a, &b);
+        assert!(!aligned);
+        assert_eq!(pairs.len(), a.len());
+    }
+
+    /// A writer whose reader went away
+    struct Closed;
+
+    impl Write for Closed {
+        fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn should_report_write_errors() {
+        let diff = Diff::parse_from(["diff", "a.bin", "b.bin"]);
+        let alignment = Alignment::new(b"abc", b"abd");
+        let ranges = alignment.differing_ranges();
+        let err = diff.print_table(&mut Closed, &alignment, 3).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::BrokenPipe);
+        assert!(diff.print_summary(&mut Closed, &ranges, true, 3).is_err());
+        assert!(diff.print_json(&mut Closed, &ranges).is_err());
+    }
+
+    #[test]
+    fn should_print_the_ranges_in_the_radix_of_the_offsets() {
+        let diff = Diff::parse_from(["diff", "-r", "d", "a.bin", "b.bin"]);
+        let ranges = Alignment::new(&[0; 20], &[1; 20]).differing_ranges();
+        let mut output = Vec::new();
+        diff.print_summary(&mut output, &ranges, true, 20).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(
+            output.ends_with("  00000000..00000020 (20 bytes) → 00000000..00000020 (20 bytes)\n")
+        );
+    }
+}
@@ -1,18 +1,58 @@
 use ratatui::DefaultTerminal;
+use std::io::{Seek, Write};
+use std::path::{Path, PathBuf};
 
-use super::row::Row;
+use super::edits::Edits;
+use super::inspector;
+use super::keys::KeyBindings;
+use super::pager::Pager;
+use super::prompt::Prompt;
+use super::search::Search;
+use super::squeeze::{self, Runs, MIN_RUN_LEN};
+use super::strings::StringList;
 use super::View;
+use hex_ray::utils::encoding::{Encoding, Glyph};
+use hex_ray::utils::format::Endianness;
+use hex_ray::utils::source::{self, Source};
+use hex_ray::Dumper;
+use hex_ray::Error;
+
+/// The column that receives the typed input in edit mode
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    #[default]
+    Hex,
+    Ascii,
+}
 
 /// The main application state
 #[derive(Debug, Default)]
 pub struct App {
-    pub cfg: View,            // Configuration parameters
-    pub data: Vec<Row>,       // The 2D vector of data
-    pub total_bytes: usize,   // The total count of bytes
-    pub selected: usize,      // The index of the selected byte
-    pub scroll_offset: usize, // The scroll position marking the first row to show
-    pub rows_per_page: usize, // Number of rows to show per page
-    pub exit: bool,           // Should exit the application
+    pub cfg: View,                     // Configuration parameters
+    pub dumper: Dumper,                // The layout of the rows, shared with the printed view
+    pub data: Pager,                   // The rows of data, paged in on-demand
+    pub compare: Option<Pager>,        // The rows of the file to compare against, if any
+    pub compare_path: Option<PathBuf>, // The path of the file to compare against
+    pub total_bytes: usize,            // The total count of bytes (in the longer of the files)
+    pub selected: usize,               // The index of the selected byte
+    pub anchor: Option<usize>, // The other end of the selected range, when selecting a range
+    pub scroll_offset: usize,  // The scroll position marking the first row to show
+    pub rows_per_page: usize,  // Number of rows to show per page
+    pub edits: Edits,          // The modified bytes that have not been saved yet
+    pub editing: bool,         // Whether typing overwrites the selected byte
+    pub focus: Focus,          // The column that receives the typed input when editing
+    pub low_nibble: bool,      // Whether the next hex digit overwrites the low nibble
+    pub endianness: Endianness, // The byte-order of the values in the data inspector
+    pub encoding: Encoding,    // The character encoding of the text column
+    pub prompt: Option<Prompt>, // The active input prompt, if any
+    pub search: Option<Search>, // The results of the most recent search
+    pub strings: Option<StringList>, // The strings listed in the side panel, while it is open
+    pub status: Option<String>, // A message to show in place of the help line
+    pub squeeze: bool,         // Whether runs of identical rows are collapsed into a `*` line
+    pub runs: Runs,            // The runs of identical rows found so far
+    pub lines: Vec<squeeze::Line>, // The lines in view, laid out before every draw
+    pub keys: KeyBindings,     // The actions bound to the keys
+    pub exit: bool,            // Should exit the application
 }
 
 impl App {
@@ -20,65 +60,280 @@ impl App {
     pub fn new(cfg: View, terminal_height: u16) -> Self {
         // The number of rows to show per scroll-page is determined by the terminal height. (Minimum: 10 rows)
         // We subtract 6 to account for the border, help-line etc. both above and below the viewport.
-        let rows_per_page = std::cmp::max(10, terminal_height as usize - 6);
+        let rows_per_page = std::cmp::max(10, (terminal_height as usize).saturating_sub(6));
         Self {
+            squeeze: !cfg.no_squeeze,
+            encoding: cfg.encoding,
+            dumper: cfg.dumper(),
             cfg,
             rows_per_page,
             ..Default::default()
         }
     }
 
-    /// Parse the data from the reader
-    pub fn parse<T>(
-        &mut self,
-        mut reader: T,
-        offset: usize,
-    ) -> Result<&mut Self, Box<dyn std::error::Error>>
-    where
-        T: std::io::BufRead,
-    {
-        // Buffer to store the data
-        let mut buffer = vec![0; self.cfg.size];
+    /// Load the data from the source.
+    ///
+    /// Rows are only read from the source as they come into view.
+    pub fn load(&mut self, source: Box<dyn Source>) -> &mut Self {
+        // Keep a few pages worth of rows around the viewport in memory
+        self.data = Pager::new(source, self.cfg.size).with_capacity(self.rows_per_page * 8);
+        self.update_total_bytes();
+        self
+    }
 
-        // The number of bytes remaining to be read
-        let mut bytes_remaining = self.cfg.limit.unwrap_or(usize::MAX);
+    /// Load the data of the file to compare against from the source.
+    ///
+    /// Its rows are shown side by side with the rows of the data, at the same positions.
+    pub fn load_compare(&mut self, source: Box<dyn Source>, path: Option<PathBuf>) -> &mut Self {
+        self.compare =
+            Some(Pager::new(source, self.cfg.size).with_capacity(self.rows_per_page * 8));
+        self.compare_path = path;
+        self.update_total_bytes();
+        self
+    }
 
-        // Keep iterating until we run out of bytes to read...
-        while bytes_remaining > 0 {
-            // Determine the number of bytes to be read in this iteration
-            let bytes_to_read = std::cmp::min(bytes_remaining, self.cfg.size);
+    /// Update the total count of bytes to cover the data, and the file to compare against
+    fn update_total_bytes(&mut self) {
+        let compare_len = self.compare.as_ref().map_or(0, Pager::len);
+        self.total_bytes = std::cmp::max(self.data.len(), compare_len);
+    }
 
-            // Read the bytes into the buffer
-            let bytes_read = reader.read(&mut buffer[0..bytes_to_read])?;
-            if bytes_read == 0 {
-                break; // break the loop if no bytes were read
-            }
+    /// Returns the current value of the byte at the given index, including any unsaved edits.
+    ///
+    /// Returns `None` if the row containing the byte has not been paged in.
+    pub fn byte(&self, index: usize) -> Option<u8> {
+        if let Some(byte) = self.edits.get(index) {
+            return Some(byte);
+        }
+        let row = self.data.get(self.row(index))?;
+        row.data.get(self.col(index)).copied()
+    }
 
-            // Add the row data to the vector
-            let row = Row::new(&buffer, offset + self.total_bytes);
-            self.data.push(row);
+    /// Returns the value of the byte at the given index in the file to compare against.
+    ///
+    /// Returns `None` if there is no such file, or if the row containing the byte has not been paged in.
+    pub fn compare_byte(&self, index: usize) -> Option<u8> {
+        let row = self.compare.as_ref()?.get(self.row(index))?;
+        row.data.get(self.col(index)).copied()
+    }
+
+    /// Returns true if the byte at the given index differs from the file to compare against.
+    ///
+    /// Bytes beyond the end of either file always differ.
+    pub fn differs(&self, index: usize) -> bool {
+        self.compare.is_some() && self.byte(index) != self.compare_byte(index)
+    }
+
+    /// Save the data, along with the edits, to the target path and return the number of bytes modified.
+    ///
+    /// When saving to a path other than the source file, the source is copied over first and
+    /// the view is then reloaded from the target. The file to compare against is never written to. Data read from STDIN is saved on its own,
+    /// so it begins at the start of the target.
+    pub fn save(&mut self, target: &Path) -> hex_ray::Result<usize> {
+        let start = match self.cfg.filepath {
+            Some(_) => self.data.start(),
+            None => 0,
+        };
+
+        // Copying over the file to compare against would truncate it while it is mapped
+        if self
+            .compare_path
+            .as_ref()
+            .is_some_and(|path| is_same_file(path, target))
+        {
+            return Err(Error::InvalidArgument(String::from(
+                "Cannot save over the file being compared against",
+            )));
+        }
+
+        let is_source = self.is_source(target);
+        if !is_source {
+            match &self.cfg.filepath {
+                Some(source) => {
+                    std::fs::copy(source, target)?;
+                }
+                None => {
+                    let mut file = std::fs::File::create(target)?;
+                    self.data.copy_to(&mut file)?;
+                }
+            }
+        }
 
-            // Update the total bytes and the number of bytes remaining
-            self.total_bytes += bytes_read;
-            bytes_remaining -= bytes_read;
+        // Write the modified bytes at their positions
+        let mut file = std::fs::OpenOptions::new().write(true).open(target)?;
+        for (index, byte) in self.edits.iter() {
+            file.seek(std::io::SeekFrom::Start((start + index) as u64))?;
+            file.write_all(&[byte])?;
         }
+        file.flush()?;
 
-        Ok(self)
+        // Reload the data from the saved file
+        let count = self.edits.len();
+        let path = target.to_path_buf();
+        let source = source::open(Some(&path), start as i64, Some(self.data.len()))?;
+        self.load(source);
+        self.cfg.filepath = Some(target.to_path_buf());
+        self.edits.clear();
+
+        Ok(count)
+    }
+
+    /// Returns true if the path leads to the file that is being viewed
+    pub fn is_source(&self, path: &Path) -> bool {
+        self.cfg
+            .filepath
+            .as_ref()
+            .is_some_and(|source| is_same_file(source, path))
     }
 
     /// Run the application in the terminal
-    pub fn run(
-        &mut self,
-        terminal: &mut DefaultTerminal,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn run(&mut self, terminal: &mut DefaultTerminal) -> hex_ray::Result<()> {
         // The main draw loop
         while !self.exit {
-            terminal.draw(|frame| self.draw(frame))?; // Render UI
-            self.handle_events()?; // Handle Events
+            // Scan the next part of the data for the strings panel
+            if let Some(list) = self.strings.as_mut() {
+                if list.scan(&mut self.data, &self.edits, self.rows_per_page)? {
+                    self.status = Some(match (list.strings.len(), list.truncated) {
+                        (0, _) => String::from("No strings found"),
+                        (n, false) => format!("Found {} strings", n),
+                        (n, true) => format!("Found more than {} strings", n),
+                    });
+                }
+            }
+
+            // Page in the visible rows, along with the bytes after the selection for the data inspector
+            self.lines = self.layout()?;
+            let mut visible: Vec<usize> = self
+                .lines
+                .iter()
+                .filter_map(|line| match line {
+                    squeeze::Line::Row(row) => Some(*row),
+                    squeeze::Line::Squeezed(_) => None,
+                })
+                .collect();
+            // ... and the rows around them that complete the characters crossing into the text column
+            let context: Vec<usize> = visible
+                .iter()
+                .flat_map(|&row| {
+                    let window = self.encoding.window(self.rows(row)..self.rows(row + 1));
+                    self.row(window.start)..=self.row(window.end - 1)
+                })
+                .collect();
+            visible.extend(context);
+            visible
+                .extend(self.row(self.selected)..=self.row(self.selected + inspector::MAX_LEN - 1));
+            self.data.load(visible.clone())?;
+            if let Some(compare) = self.compare.as_mut() {
+                compare.load(visible)?;
+            }
+            terminal
+                .draw(|frame| self.draw(frame))
+                .map_err(Error::Terminal)?; // Render UI
+            self.handle_events().map_err(Error::Terminal)?; // Handle Events
         }
         Ok(())
     }
 
+    /// Lay out the lines in view, from the row at the scroll offset down.
+    ///
+    /// When squeezing, the rows between the first and last rows of a run of identical rows are
+    /// collapsed into a single `*` line. The selected row is always kept in view.
+    pub fn layout(&mut self) -> std::io::Result<Vec<squeeze::Line>> {
+        let rows = self.total_bytes.div_ceil(self.cfg.size);
+        let selected = self.row(self.selected);
+
+        let mut lines = Vec::new();
+        let mut row = self.scroll_offset;
+        while lines.len() < self.rows_per_page && row < rows {
+            lines.push(squeeze::Line::Row(row));
+            row += 1;
+            if !self.is_squeezing() {
+                continue;
+            }
+
+            let run = self.runs.get(&mut self.data, &self.edits, row - 1)?;
+            if run.len() >= MIN_RUN_LEN && row < run.end - 1 {
+                let end = match (row..run.end - 1).contains(&selected) {
+                    true => selected,
+                    false => run.end - 1,
+                };
+                if end > row {
+                    lines.push(squeeze::Line::Squeezed(row..end));
+                }
+                row = end;
+            }
+        }
+        Ok(lines)
+    }
+
+    /// Returns true if runs of identical rows are collapsed; never while comparing files,
+    /// to keep the rows of both files aligned
+    pub fn is_squeezing(&self) -> bool {
+        self.squeeze && self.compare.is_none()
+    }
+
+    /// Returns the row in view below the given row, stepping over collapsed rows
+    pub fn row_below(&mut self, row: usize) -> usize {
+        if self.is_squeezing() {
+            if let Ok(run) = self.runs.get(&mut self.data, &self.edits, row) {
+                if run.len() >= MIN_RUN_LEN && row + 1 < run.end - 1 {
+                    return run.end - 1;
+                }
+            }
+        }
+        row + 1
+    }
+
+    /// Returns the row in view above the given row, stepping over collapsed rows
+    pub fn row_above(&mut self, row: usize) -> usize {
+        if self.is_squeezing() {
+            if let Ok(run) = self.runs.get(&mut self.data, &self.edits, row) {
+                if run.len() >= MIN_RUN_LEN && row > run.start + 1 {
+                    return run.start;
+                }
+            }
+        }
+        row.saturating_sub(1)
+    }
+
+    /// Returns the glyphs of the text column for the `data` of the row, decoded along with the
+    /// bytes of the rows around it (either of the data or of the file to compare against)
+    pub fn glyphs(&self, row: usize, data: &[u8], compare: bool) -> Vec<Glyph> {
+        let byte = |index: usize| match compare {
+            true => self.compare_byte(index),
+            false => self.byte(index),
+        };
+        let row = self.rows(row)..self.rows(row) + data.len();
+        let window = self.encoding.window(row.clone());
+
+        // The rows around the row are paged in along with it; otherwise, decode the row on its own
+        let mut bytes: Vec<u8> = (window.start..row.start).map_while(byte).collect();
+        if bytes.len() < row.start - window.start {
+            bytes.clear();
+        }
+        let start = bytes.len();
+        bytes.extend(data);
+        bytes.extend((row.end..window.end).map_while(byte));
+        self.encoding.decode_row(&bytes, start..start + data.len())
+    }
+
+    /// Get the first and last index of the selected range (which is just the selected byte
+    /// when not selecting a range)
+    pub fn range(&self) -> (usize, usize) {
+        let anchor = self.anchor.unwrap_or(self.selected);
+        (
+            std::cmp::min(anchor, self.selected),
+            std::cmp::max(anchor, self.selected),
+        )
+    }
+
+    /// The number of digits in the offset column, wide enough to fit the offsets of both files
+    pub fn offset_width(&self) -> usize {
+        let end = |data: &Pager| data.start() + data.len();
+        let max_offset = std::cmp::max(end(&self.data), self.compare.as_ref().map_or(0, end));
+        self.cfg.radix.width(max_offset as u64)
+    }
+
     /// Get the row number for the given index position
     pub fn row(&self, index: usize) -> usize {
         index / self.cfg.size
@@ -93,4 +348,78 @@ impl App {
     pub fn rows(&self, n: usize) -> usize {
         n * self.cfg.size
     }
+
+    /// Get the index of the last byte
+    pub fn last(&self) -> usize {
+        self.total_bytes.saturating_sub(1)
+    }
+}
+
+/// Returns true if both paths lead to the same file, whether through links or otherwise
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        if let (Ok(a), Ok(b)) = (std::fs::metadata(a), std::fs::metadata(b)) {
+            return (a.dev(), a.ino()) == (b.dev(), b.ino());
+        }
+    }
+    matches!((a.canonicalize(), b.canonicalize()), (Ok(a), Ok(b)) if a == b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write the bytes to a new file in the temporary directory
+    fn temp_file(name: &str, data: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("hex-ray-{}-{}", std::process::id(), name));
+        std::fs::write(&path, data).unwrap();
+        path
+    }
+
+    /// An app viewing the file at the path
+    fn app(path: &Path) -> App {
+        let mut app = App::default();
+        app.cfg.size = 16;
+        app.cfg.filepath = Some(path.to_path_buf());
+        app.load(source::open(Some(&path.to_path_buf()), 0, None).unwrap());
+        app
+    }
+
+    #[test]
+    fn should_not_save_over_the_file_being_compared_against() {
+        let path = temp_file("save-source", b"abcd");
+        let other = temp_file("save-compare", b"abce");
+        let mut app = app(&path);
+        app.load_compare(
+            source::open(Some(&other), 0, None).unwrap(),
+            Some(other.clone()),
+        );
+        app.edits.set(0, b'x');
+
+        assert!(app.save(&other).is_err());
+        assert_eq!(std::fs::read(&other).unwrap(), b"abce");
+        assert_eq!(app.edits.len(), 1);
+
+        std::fs::remove_file(path).unwrap();
+        std::fs::remove_file(other).unwrap();
+    }
+
+    #[test]
+    fn should_save_through_links_to_the_source_in_place() {
+        let path = temp_file("save-linked", b"abcd");
+        let link = path.with_extension("link");
+        let _ = std::fs::remove_file(&link);
+        std::fs::hard_link(&path, &link).unwrap();
+        let mut app = app(&path);
+        assert!(app.is_source(&link));
+
+        app.edits.set(1, b'x');
+        assert_eq!(app.save(&link).unwrap(), 1);
+        assert_eq!(std::fs::read(&path).unwrap(), b"axcd");
+
+        std::fs::remove_file(path).unwrap();
+        std::fs::remove_file(link).unwrap();
+    }
 }
@@ -0,0 +1,146 @@
+// Library
+use std::ops::Range;
+
+use super::edits::Edits;
+use super::pager::Pager;
+
+/// The number of bytes to compare at a time
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Returns the index of the first byte after `index` that differs between the data (along with
+/// the unsaved edits) and the file to compare against, wrapping around to the start
+pub fn next_difference(
+    data: &mut Pager,
+    edits: &Edits,
+    compare: &mut Pager,
+    index: usize,
+) -> std::io::Result<Option<usize>> {
+    let end = std::cmp::max(data.len(), compare.len());
+    let after = index.saturating_add(1).min(end);
+    match first_difference(data, edits, compare, after..end)? {
+        Some(index) => Ok(Some(index)),
+        None => first_difference(data, edits, compare, 0..after),
+    }
+}
+
+/// Returns the index of the last byte before `index` that differs between the data (along with
+/// the unsaved edits) and the file to compare against, wrapping around to the end
+pub fn previous_difference(
+    data: &mut Pager,
+    edits: &Edits,
+    compare: &mut Pager,
+    index: usize,
+) -> std::io::Result<Option<usize>> {
+    let end = std::cmp::max(data.len(), compare.len());
+    let before = index.min(end);
+    match last_difference(data, edits, compare, 0..before)? {
+        Some(index) => Ok(Some(index)),
+        None => last_difference(data, edits, compare, before..end),
+    }
+}
+
+/// Returns the index of the first differing byte in the range
+fn first_difference(
+    data: &mut Pager,
+    edits: &Edits,
+    compare: &mut Pager,
+    range: Range<usize>,
+) -> std::io::Result<Option<usize>> {
+    let mut position = range.start;
+    while position < range.end {
+        let len = std::cmp::min(CHUNK_SIZE, range.end - position);
+        let differences = compare_chunk(data, edits, compare, position, len)?;
+        if let Some(i) = differences.iter().position(|d| *d) {
+            return Ok(Some(position + i));
+        }
+        position += len;
+    }
+    Ok(None)
+}
+
+/// Returns the index of the last differing byte in the range
+fn last_difference(
+    data: &mut Pager,
+    edits: &Edits,
+    compare: &mut Pager,
+    range: Range<usize>,
+) -> std::io::Result<Option<usize>> {
+    let mut end = range.end;
+    while end > range.start {
+        let position = std::cmp::max(range.start, end.saturating_sub(CHUNK_SIZE));
+        let differences = compare_chunk(data, edits, compare, position, end - position)?;
+        if let Some(i) = differences.iter().rposition(|d| *d) {
+            return Ok(Some(position + i));
+        }
+        end = position;
+    }
+    Ok(None)
+}
+
+/// Compare `len` bytes beginning at the given position, and return whether each of them differs.
+///
+/// Bytes beyond the end of either file always differ.
+fn compare_chunk(
+    data: &mut Pager,
+    edits: &Edits,
+    compare: &mut Pager,
+    position: usize,
+    len: usize,
+) -> std::io::Result<Vec<bool>> {
+    let range = position..position + len;
+    let mut left = data.bytes(range.clone())?;
+    if edits.modifies(range.clone()) {
+        edits.apply(position, left.to_mut());
+    }
+    let right = compare.bytes(range)?;
+    Ok((0..len)
+        .map(|i| i >= left.len() || i >= right.len() || left[i] != right[i])
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_ray::utils::source::Buffered;
+
+    fn pager(data: &[u8]) -> Pager {
+        Pager::new(Box::new(Buffered::from(data.to_vec())), 16)
+    }
+
+    #[test]
+    fn should_find_the_next_and_previous_differences() {
+        let (mut a, mut b) = (pager(b"abcdefgh"), pager(b"abXdefYh"));
+        let edits = Edits::default();
+        assert_eq!(next_difference(&mut a, &edits, &mut b, 0).unwrap(), Some(2));
+        assert_eq!(next_difference(&mut a, &edits, &mut b, 2).unwrap(), Some(6));
+        assert_eq!(next_difference(&mut a, &edits, &mut b, 6).unwrap(), Some(2));
+        assert_eq!(
+            previous_difference(&mut a, &edits, &mut b, 6).unwrap(),
+            Some(2)
+        );
+        assert_eq!(
+            previous_difference(&mut a, &edits, &mut b, 2).unwrap(),
+            Some(6)
+        );
+    }
+
+    #[test]
+    fn should_treat_bytes_beyond_the_end_as_different() {
+        let (mut a, mut b) = (pager(b"abcd"), pager(b"abcdef"));
+        let edits = Edits::default();
+        assert_eq!(next_difference(&mut a, &edits, &mut b, 0).unwrap(), Some(4));
+        assert_eq!(
+            previous_difference(&mut a, &edits, &mut b, 0).unwrap(),
+            Some(5)
+        );
+    }
+
+    #[test]
+    fn should_compare_the_unsaved_edits() {
+        let (mut a, mut b) = (pager(b"abcd"), pager(b"abXd"));
+        let mut edits = Edits::default();
+        edits.set(2, b'X');
+        edits.set(3, b'Y');
+        assert_eq!(next_difference(&mut a, &edits, &mut b, 0).unwrap(), Some(3));
+    }
+}
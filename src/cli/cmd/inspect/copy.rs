@@ -0,0 +1,140 @@
+// Library
+use std::io::Write;
+
+/// The characters used to encode base64
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// The maximum number of bytes to send to the clipboard; terminals tend to drop longer sequences
+const MAX_CLIPBOARD_LEN: usize = 1024 * 1024;
+
+/// The representation in which to copy out the selected bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// A string of hex digits (e.g. `deadbeef`)
+    Hex,
+    /// A C array declaration (e.g. `unsigned char data[] = { 0xde, 0xad };`)
+    CArray,
+    /// A base64 encoded string
+    Base64,
+    /// The bytes as they are
+    Raw,
+}
+
+impl Encoding {
+    /// Parse the name of the encoding, as entered in the prompt
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "hex" | "x" => Some(Self::Hex),
+            "c" | "array" => Some(Self::CArray),
+            "base64" | "b64" => Some(Self::Base64),
+            "raw" | "bin" => Some(Self::Raw),
+            _ => None,
+        }
+    }
+
+    /// The name of the encoding, for the status messages
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Hex => "hex",
+            Self::CArray => "a C array",
+            Self::Base64 => "base64",
+            Self::Raw => "raw bytes",
+        }
+    }
+
+    /// Encode the bytes in this representation
+    pub fn encode(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Hex => data
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>()
+                .into_bytes(),
+            Self::CArray => {
+                let rows: Vec<String> = data
+                    .chunks(12)
+                    .map(|row| {
+                        let values: Vec<String> =
+                            row.iter().map(|b| format!("0x{:02x}", b)).collect();
+                        format!("    {},", values.join(", "))
+                    })
+                    .collect();
+                format!(
+                    "unsigned char data[{}] = {{\n{}\n}};\n",
+                    data.len(),
+                    rows.join("\n")
+                )
+                .into_bytes()
+            }
+            Self::Base64 => base64(data).into_bytes(),
+            Self::Raw => data.to_vec(),
+        }
+    }
+}
+
+/// Encode the bytes as base64 (with padding)
+fn base64(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bits = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |acc, (i, b)| acc | (*b as u32) << (16 - i * 8));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (bits >> (18 - i * 6)) & 0x3f;
+                encoded.push(BASE64_ALPHABET[index as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Copy the bytes to the system clipboard using the OSC 52 terminal escape sequence.
+///
+/// The terminal (or multiplexer) has to support OSC 52 for this to work, which
+/// also lets the clipboard be set from over an SSH connection.
+pub fn to_clipboard(data: &[u8]) -> Result<(), String> {
+    if data.len() > MAX_CLIPBOARD_LEN {
+        return Err(String::from(
+            "The selection is too large for the clipboard; copy it to a file instead",
+        ));
+    }
+    let mut stdout = std::io::stdout();
+    stdout
+        .write_all(osc52(data).as_bytes())
+        .and_then(|_| stdout.flush())
+        .map_err(|e| e.to_string())
+}
+
+/// The OSC 52 escape sequence that sets the clipboard to the bytes
+fn osc52(data: &[u8]) -> String {
+    format!("\x1b]52;c;{}\x07", base64(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_encode_the_bytes() {
+        let data = [0xde, 0xad, 0xbe, 0xef];
+        assert_eq!(Encoding::Hex.encode(&data), b"deadbeef");
+        assert_eq!(
+            String::from_utf8(Encoding::CArray.encode(&data)).unwrap(),
+            "unsigned char data[4] = {\n    0xde, 0xad, 0xbe, 0xef,\n};\n"
+        );
+        assert_eq!(Encoding::Raw.encode(&data), data);
+    }
+
+    #[test]
+    fn should_encode_base64_with_padding() {
+        assert_eq!(base64(b"hex"), "aGV4");
+        assert_eq!(base64(b"hex-"), "aGV4LQ==");
+        assert_eq!(base64(b"hex-r"), "aGV4LXI=");
+        assert_eq!(osc52(b"hi"), "\x1b]52;c;aGk=\x07");
+    }
+}
@@ -0,0 +1,219 @@
+// Library
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// A modification of a run of bytes, as recorded in the journal
+#[derive(Debug, Clone)]
+struct Change {
+    index: usize,            // The index of the first modified byte
+    before: Vec<Option<u8>>, // The unsaved values before the change (`None` where unmodified)
+    after: Vec<u8>,          // The values after the change
+}
+
+/// The bytes that have been modified but not yet saved, keyed by their index.
+///
+/// Every modification is recorded in a journal so that it can be undone and redone.
+#[derive(Debug, Default)]
+pub struct Edits {
+    bytes: BTreeMap<usize, u8>,
+    undo: Vec<Change>,      // The changes that can be undone, most recent last
+    redo: Vec<Change>,      // The changes that have been undone, most recent last
+    log: Vec<Range<usize>>, // The indices of every change since the last discard, so that state derived from the bytes can tell what is stale
+    discarded: usize,       // The number of revisions that are no longer in the log
+}
+
+impl Edits {
+    /// Returns the modified value of the byte at the given index, if it was modified
+    pub fn get(&self, index: usize) -> Option<u8> {
+        self.bytes.get(&index).copied()
+    }
+
+    /// Overwrite the byte at the given index
+    pub fn set(&mut self, index: usize, byte: u8) {
+        self.set_many(index, &[byte]);
+    }
+
+    /// Overwrite the bytes beginning at the given index, as a single change that is undone and
+    /// redone as a whole
+    pub fn set_many(&mut self, index: usize, bytes: &[u8]) {
+        let before = self.write(index, bytes);
+        self.undo.push(Change {
+            index,
+            before,
+            after: bytes.to_vec(),
+        });
+        self.redo.clear();
+    }
+
+    /// Overwrite the byte at the given index as a part of the most recent change to it.
+    ///
+    /// Falls back to [`Edits::set()`] if the most recent change was to a different byte.
+    pub fn amend(&mut self, index: usize, byte: u8) {
+        match self.undo.last_mut() {
+            Some(change) if change.index == index && change.after.len() == 1 => {
+                change.after = vec![byte];
+                self.write(index, &[byte]);
+                self.redo.clear();
+            }
+            _ => self.set(index, byte),
+        }
+    }
+
+    /// Revert the most recent change and return the index of the first byte it affected
+    pub fn undo(&mut self) -> Option<usize> {
+        let change = self.undo.pop()?;
+        for (i, before) in change.before.iter().enumerate() {
+            let index = change.index + i;
+            match before {
+                Some(byte) => self.bytes.insert(index, *byte),
+                None => self.bytes.remove(&index),
+            };
+        }
+        self.log
+            .push(change.index..change.index + change.before.len());
+        let index = change.index;
+        self.redo.push(change);
+        Some(index)
+    }
+
+    /// Reapply the most recently undone change and return the index of the first byte it affected
+    pub fn redo(&mut self) -> Option<usize> {
+        let change = self.redo.pop()?;
+        self.write(change.index, &change.after);
+        let index = change.index;
+        self.undo.push(change);
+        Some(index)
+    }
+
+    /// Write the bytes beginning at the given index, and return the unsaved values they replaced
+    fn write(&mut self, index: usize, bytes: &[u8]) -> Vec<Option<u8>> {
+        let mut before = Vec::with_capacity(bytes.len());
+        for (i, byte) in bytes.iter().enumerate() {
+            before.push(self.bytes.insert(index + i, *byte));
+        }
+        self.log.push(index..index + bytes.len());
+        before
+    }
+
+    /// The number of modified bytes
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Returns true if nothing has been modified
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Iterate over the modified bytes in order of their index
+    pub fn iter(&self) -> impl Iterator<Item = (usize, u8)> + '_ {
+        self.bytes.iter().map(|(index, byte)| (*index, *byte))
+    }
+
+    /// Discard all modifications along with the journal and the log
+    pub fn clear(&mut self) {
+        self.bytes.clear();
+        self.undo.clear();
+        self.redo.clear();
+        self.discarded = self.revision() + 1;
+        self.log.clear();
+    }
+
+    /// The number of modifications made so far (including undoing, redoing and discarding them)
+    pub fn revision(&self) -> usize {
+        self.discarded + self.log.len()
+    }
+
+    /// Returns the ranges of indices modified since the given revision, or `None` if all of
+    /// them may have changed
+    pub fn changed_since(&self, revision: usize) -> Option<Vec<Range<usize>>> {
+        let start = revision.checked_sub(self.discarded)?;
+        self.log.get(start..).map(<[_]>::to_vec)
+    }
+
+    /// Returns true if any of the bytes in the given range of indices were modified
+    pub fn modifies(&self, range: Range<usize>) -> bool {
+        self.bytes.range(range).next().is_some()
+    }
+
+    /// Overlay the modifications onto the given bytes that begin at the `start` index
+    pub fn apply(&self, start: usize, data: &mut [u8]) {
+        for (index, byte) in self.bytes.range(start..start + data.len()) {
+            data[index - start] = *byte;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_undo_and_redo_changes() {
+        let mut edits = Edits::default();
+        edits.set(3, 0xaa);
+        edits.set(3, 0xbb);
+        edits.set(7, 0xcc);
+
+        assert_eq!(edits.undo(), Some(7));
+        assert_eq!(edits.get(7), None);
+        assert_eq!(edits.undo(), Some(3));
+        assert_eq!(edits.get(3), Some(0xaa));
+        assert_eq!(edits.undo(), Some(3));
+        assert!(edits.is_empty());
+        assert_eq!(edits.undo(), None);
+
+        assert_eq!(edits.redo(), Some(3));
+        assert_eq!(edits.redo(), Some(3));
+        assert_eq!(edits.get(3), Some(0xbb));
+    }
+
+    #[test]
+    fn should_discard_redo_history_on_new_changes() {
+        let mut edits = Edits::default();
+        edits.set(0, 0x01);
+        edits.undo();
+        edits.set(1, 0x02);
+        assert_eq!(edits.redo(), None);
+        assert_eq!(edits.get(0), None);
+    }
+
+    #[test]
+    fn should_amend_the_most_recent_change() {
+        let mut edits = Edits::default();
+        edits.set(0, 0xa0);
+        edits.amend(0, 0xab);
+        assert_eq!(edits.get(0), Some(0xab));
+        assert_eq!(edits.undo(), Some(0));
+        assert!(edits.is_empty());
+    }
+
+    #[test]
+    fn should_undo_grouped_changes_as_a_whole() {
+        let mut edits = Edits::default();
+        edits.set(1, 0xaa);
+        edits.set_many(0, &[0xc3, 0xa9]);
+        assert_eq!(edits.iter().collect::<Vec<_>>(), vec![(0, 0xc3), (1, 0xa9)]);
+
+        assert_eq!(edits.undo(), Some(0));
+        assert_eq!(edits.iter().collect::<Vec<_>>(), vec![(1, 0xaa)]);
+        assert_eq!(edits.redo(), Some(0));
+        assert_eq!(edits.get(1), Some(0xa9));
+    }
+
+    #[test]
+    fn should_log_the_changed_ranges_until_they_are_discarded() {
+        let mut edits = Edits::default();
+        edits.set_many(4, &[0xc3, 0xa9]);
+        edits.undo();
+        assert_eq!(edits.changed_since(0), Some(vec![4..6, 4..6]));
+        assert_eq!(edits.changed_since(2), Some(vec![]));
+
+        edits.clear();
+        assert_eq!(edits.revision(), 3);
+        assert_eq!(edits.changed_since(1), None);
+        edits.set(0, 0x01);
+        edits.set(8, 0x02);
+        assert_eq!(edits.changed_since(3), Some(vec![0..1, 8..9]));
+    }
+}
@@ -1,10 +1,25 @@
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 
+use super::app::Focus;
+use super::compare;
+use super::copy::{self, Encoding};
+use super::goto;
+use super::keys::Action;
+use super::prompt::{Prompt, PromptKind};
+use super::search::Search;
+use super::strings::StringList;
 use super::App;
+use clap::ValueEnum;
+use hex_ray::utils::format::Endianness;
 
 impl App {
     /// updates the application's state based on user input
     pub fn handle_events(&mut self) -> std::io::Result<()> {
+        // Keep on drawing frames while the strings are being scanned, rather than waiting for a key
+        let scanning = self.strings.as_ref().is_some_and(StringList::is_scanning);
+        if scanning && !event::poll(std::time::Duration::ZERO)? {
+            return Ok(());
+        }
         match event::read()? {
             // it's important to check that the event is a key press event as
             // crossterm also emits key release and repeat events on Windows.
@@ -18,19 +33,180 @@ impl App {
 
     /// matches the given key-event and calls the corresponding handler
     fn handle_key_event(&mut self, key_event: KeyEvent) {
+        // The prompt captures all input while it is open
+        if self.prompt.is_some() {
+            return self.handle_prompt_key_event(key_event);
+        }
+
+        // Clear the status message on the next key press
+        self.status = None;
+
+        let selected = self.selected;
+        self.handle_command_key_event(key_event);
+
+        // Start over from the high nibble whenever the selection moves
+        if self.selected != selected {
+            self.low_nibble = false;
+        }
+    }
+
+    /// handles the key-events outside of the prompt
+    fn handle_command_key_event(&mut self, key_event: KeyEvent) {
         match key_event.code {
-            KeyCode::Up => self.move_selection_up(),
-            KeyCode::Right => self.move_selection_right(),
-            KeyCode::Down => self.move_selection_down(),
-            KeyCode::Left => self.move_selection_left(),
+            KeyCode::Char('s') if key_event.modifiers == KeyModifiers::CONTROL => {
+                return self.open_save_prompt()
+            }
+            KeyCode::Char('z') if key_event.modifiers == KeyModifiers::CONTROL => {
+                return self.undo()
+            }
+            KeyCode::Char('y') if key_event.modifiers == KeyModifiers::CONTROL => {
+                return self.redo()
+            }
+            _ => {}
+        }
+
+        // Typed characters overwrite the selected byte in edit mode, whatever they are bound to
+        let is_typed = matches!(key_event.code, KeyCode::Char(_) | KeyCode::Tab)
+            && !key_event.modifiers.contains(KeyModifiers::CONTROL);
+        let action = match self.editing && is_typed {
+            true => None,
+            false => self.keys.action(key_event),
+        };
+
+        // The strings panel takes the keys that move through its list while it is open
+        if !self.editing && self.handle_strings_key_event(key_event, action) {
+            return;
+        }
+
+        // Moving the selection while holding shift selects a range
+        if action.is_some_and(Action::is_movement)
+            && key_event.modifiers.contains(KeyModifiers::SHIFT)
+        {
+            self.anchor.get_or_insert(self.selected);
+        }
+
+        match action {
+            Some(Action::Up) => self.move_selection_up(),
+            Some(Action::Right) => self.move_selection_right(),
+            Some(Action::Down) => self.move_selection_down(),
+            Some(Action::Left) => self.move_selection_left(),
+
+            Some(Action::Home) => self.move_selection_to_home(key_event.modifiers),
+            Some(Action::End) => self.move_selection_to_end(key_event.modifiers),
 
-            KeyCode::Home => self.move_selection_to_home(key_event.modifiers),
-            KeyCode::End => self.move_selection_to_end(key_event.modifiers),
+            Some(Action::PageUp) => self.scroll_up(),
+            Some(Action::PageDown) => self.scroll_down(),
 
-            KeyCode::PageUp => self.scroll_up(),
-            KeyCode::PageDown => self.scroll_down(),
+            _ if self.editing => self.handle_edit_key_event(key_event),
 
-            KeyCode::Esc | KeyCode::Char('q') => self.exit(),
+            Some(Action::Edit) => self.editing = true,
+            Some(Action::Save) => self.open_save_prompt(),
+            Some(Action::Undo) => self.undo(),
+            Some(Action::Redo) => self.redo(),
+            Some(Action::Search) => self.prompt = Some(Prompt::new(PromptKind::Search, "")),
+            Some(Action::GoTo) => self.prompt = Some(Prompt::new(PromptKind::GoTo, "")),
+            Some(Action::NextMatch) => self.jump_to_next_match(),
+            Some(Action::PreviousMatch) => self.jump_to_previous_match(),
+            Some(Action::NextDifference) => self.jump_to_next_difference(),
+            Some(Action::PreviousDifference) => self.jump_to_previous_difference(),
+            Some(Action::Endianness) => self.toggle_endianness(),
+            Some(Action::Encoding) => self.cycle_encoding(),
+            Some(Action::SelectRange) => self.toggle_range_selection(),
+            Some(Action::Squeeze) => self.toggle_squeeze(),
+            Some(Action::Strings) => self.toggle_strings(),
+            Some(Action::Copy) => self.prompt = Some(Prompt::new(PromptKind::Copy, "hex")),
+            Some(Action::Quit) => self.request_exit(),
+
+            None if key_event.code == KeyCode::Esc => match self.anchor {
+                Some(_) => self.anchor = None,
+                None => self.request_exit(),
+            },
+            None => {}
+        }
+    }
+
+    /// handles the key-events in edit mode
+    fn handle_edit_key_event(&mut self, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Insert => {
+                self.editing = false;
+                self.low_nibble = false;
+            }
+            KeyCode::Tab => {
+                self.focus = match self.focus {
+                    Focus::Hex => Focus::Ascii,
+                    Focus::Ascii => Focus::Hex,
+                };
+                self.low_nibble = false;
+            }
+            KeyCode::Char(c) => match self.focus {
+                Focus::Hex => self.write_nibble(c),
+                Focus::Ascii => self.write_char(c),
+            },
+            _ => {}
+        }
+    }
+
+    /// handles the key-events while the strings panel is open. Returns true if the key was
+    /// used by the panel; the highlighted string is selected as the list is moved through
+    fn handle_strings_key_event(&mut self, key_event: KeyEvent, action: Option<Action>) -> bool {
+        let Some(list) = self.strings.as_mut() else {
+            return false;
+        };
+        let selected = match action {
+            Some(Action::Up) => list.selected.saturating_sub(1),
+            Some(Action::Down) => list.selected + 1,
+            Some(Action::PageUp) => list.selected.saturating_sub(self.rows_per_page),
+            Some(Action::PageDown) => list.selected + self.rows_per_page,
+            Some(Action::Home) => 0,
+            Some(Action::End) => usize::MAX,
+            Some(Action::Strings) => {
+                self.strings = None;
+                return true;
+            }
+            _ if matches!(key_event.code, KeyCode::Enter | KeyCode::Esc) => {
+                self.strings = None;
+                return true;
+            }
+            _ => return false,
+        };
+        if let Some(index) = list.select(selected, self.rows_per_page) {
+            self.anchor = None;
+            self.select(index);
+        }
+        true
+    }
+
+    /// handles the key-events while the prompt is open
+    fn handle_prompt_key_event(&mut self, key_event: KeyEvent) {
+        let Some(prompt) = self.prompt.as_mut() else {
+            return;
+        };
+
+        // Confirmations only need a single key press
+        if prompt.is_confirmation() {
+            if let Some(prompt) = self.prompt.take() {
+                if let KeyCode::Char('y' | 'Y') = key_event.code {
+                    match prompt.kind {
+                        PromptKind::ConfirmOverwrite => self.write_to(&prompt.input),
+                        _ => self.exit(),
+                    }
+                }
+            }
+            return;
+        }
+
+        match key_event.code {
+            KeyCode::Char(c) => prompt.input.push(c),
+            KeyCode::Backspace => {
+                prompt.input.pop();
+            }
+            KeyCode::Esc => self.prompt = None,
+            KeyCode::Enter => {
+                if let Some(prompt) = self.prompt.take() {
+                    self.submit_prompt(prompt);
+                }
+            }
             _ => {}
         }
     }
@@ -42,9 +218,11 @@ impl App {
     // Select the element in the row above
     fn move_selection_up(&mut self) {
         // Only if the selection is beyond the first row ...
-        if self.row(self.selected) >= 1 {
-            // ...Subtract the size of a row to move the selection up by 1 row
-            self.selected = self.selected.saturating_sub(self.rows(1));
+        let row = self.row(self.selected);
+        if row >= 1 {
+            // ...Subtract the size of the rows up to the row above (which may skip over collapsed rows)
+            let rows = row - self.row_above(row);
+            self.selected = self.selected.saturating_sub(self.rows(rows));
             self.adjust_scroll_view();
         }
     }
@@ -61,8 +239,11 @@ impl App {
     // Select the element in the row above
     fn move_selection_down(&mut self) {
         // Only if the selection is not in the last row ...
-        if self.row(self.selected) < self.row(self.total_bytes) {
-            self.selected += self.rows(1); // Move it down by one row
+        let row = self.row(self.selected);
+        if row < self.row(self.last()) {
+            // Move it down to the row below (which may skip over collapsed rows)
+            let rows = self.row_below(row) - row;
+            self.selected = std::cmp::min(self.selected + self.rows(rows), self.last());
             self.adjust_scroll_view();
         }
     }
@@ -70,7 +251,7 @@ impl App {
     /// Select the next element
     fn move_selection_right(&mut self) {
         // Only if the selection is not the last element ...
-        if self.selected < self.total_bytes {
+        if self.selected < self.last() {
             self.selected += 1; // ... Move it to the right by one
             self.adjust_scroll_view();
         }
@@ -78,7 +259,7 @@ impl App {
 
     /// Select the first element in the row
     fn move_selection_to_home(&mut self, modifiers: KeyModifiers) {
-        if modifiers == KeyModifiers::CONTROL {
+        if modifiers.contains(KeyModifiers::CONTROL) {
             self.selected = 0;
             self.scroll_offset = 0;
         } else {
@@ -88,12 +269,13 @@ impl App {
 
     /// Select the last element in the row
     fn move_selection_to_end(&mut self, modifiers: KeyModifiers) {
-        if modifiers == KeyModifiers::CONTROL {
-            self.selected = self.total_bytes; // The last byte
-                                              // Go to the last row, but keep one page worth of offset
-            self.scroll_offset = self.row(self.total_bytes) - self.rows_per_page + 1;
+        if modifiers.contains(KeyModifiers::CONTROL) {
+            self.selected = self.last(); // The last byte
+                                         // Go to the last row, but keep one page worth of offset
+            self.scroll_offset = (self.row(self.last()) + 1).saturating_sub(self.rows_per_page);
         } else {
             self.selected += self.cfg.size - self.col(self.selected) - 1;
+            self.selected = std::cmp::min(self.selected, self.last());
         }
     }
 
@@ -114,9 +296,9 @@ impl App {
     /// Scroll down a page
     fn scroll_down(&mut self) {
         // If the selected element is in the last page ...
-        if self.selected >= self.total_bytes - self.rows(self.rows_per_page) {
+        if self.selected >= self.last().saturating_sub(self.rows(self.rows_per_page)) {
             // ... set it to be the last element
-            self.selected = self.total_bytes + 1;
+            self.selected = self.last();
         } else {
             // Otherwise, go down one page
             self.selected += self.rows(self.rows_per_page);
@@ -126,10 +308,27 @@ impl App {
             // Scroll down one page
             self.scroll_offset += self.rows_per_page;
         }
+        // Don't scroll past the last page
+        let last_page = (self.row(self.last()) + 1).saturating_sub(self.rows_per_page);
+        self.scroll_offset = std::cmp::min(self.scroll_offset, last_page);
+        self.adjust_scroll_view();
+    }
+
+    /// Scroll such that the selection is in the middle of the view
+    fn centre_scroll_view(&mut self) {
+        let last_page = (self.row(self.last()) + 1).saturating_sub(self.rows_per_page);
+        let offset = self
+            .row(self.selected)
+            .saturating_sub(self.rows_per_page / 2);
+        self.scroll_offset = std::cmp::min(offset, last_page);
     }
 
     /// Adjust the scroll offset based on the current position of the selection
     fn adjust_scroll_view(&mut self) {
+        if self.is_squeezing() {
+            return self.adjust_squeezed_scroll_view();
+        }
+
         // Now, if the selection falls above the first row in the view ...
         if self.selected < self.rows(self.scroll_offset) {
             let rows_to_scroll = self.row(self.rows(self.scroll_offset + 1) - self.selected);
@@ -142,8 +341,369 @@ impl App {
         }
     }
 
+    /// Adjust the scroll offset based on the current position of the selection, counting the
+    /// `*` lines of collapsed rows rather than the rows themselves
+    fn adjust_squeezed_scroll_view(&mut self) {
+        let row = self.row(self.selected);
+        if row < self.scroll_offset {
+            self.scroll_offset = row;
+            return;
+        }
+
+        // Walk up from the selection until reaching the top of the view, or a page worth of lines
+        let mut top = row;
+        let mut lines = 1;
+        while top > self.scroll_offset {
+            let above = self.row_above(top);
+            let count = if top - above > 1 { 2 } else { 1 }; // The row, and the `*` line between
+            if lines + count > self.rows_per_page {
+                break;
+            }
+            lines += count;
+            top = above;
+        }
+        // If the selection falls below the view, scroll down so that it is on the last line
+        if top > self.scroll_offset {
+            self.scroll_offset = top;
+        }
+    }
+
+    /// Overwrite one nibble of the selected byte with the hex digit.
+    ///
+    /// The first digit overwrites the high nibble and the second the low nibble,
+    /// after which the selection moves on to the next byte.
+    fn write_nibble(&mut self, c: char) {
+        let (Some(digit), Some(byte)) = (c.to_digit(16), self.byte(self.selected)) else {
+            return;
+        };
+        let digit = digit as u8;
+
+        if self.low_nibble {
+            self.edits.amend(self.selected, (byte & 0xf0) | digit);
+            self.move_selection_right();
+        } else {
+            self.edits.set(self.selected, (digit << 4) | (byte & 0x0f));
+            self.low_nibble = true;
+        }
+    }
+
+    /// Overwrite the selected bytes with the character (in the encoding of the text column) and
+    /// move on to the byte after them
+    fn write_char(&mut self, c: char) {
+        let Some(bytes) = self.encoding.encode(c) else {
+            self.status = Some(format!(
+                "{} cannot be encoded in {}",
+                c,
+                self.encoding.name()
+            ));
+            return;
+        };
+        // Only overwrite bytes that exist. They need not be paged in, as the edits are kept apart
+        if self.selected + bytes.len() > self.data.len() {
+            self.status = Some(format!(
+                "{} takes {} bytes and does not fit before the end of the data",
+                c,
+                bytes.len()
+            ));
+            return;
+        }
+        // The bytes of the character are undone and redone together
+        self.edits.set_many(self.selected, &bytes);
+        for _ in 0..bytes.len() {
+            self.move_selection_right();
+        }
+    }
+
+    /// Open the prompt to save the changes, starting with the path of the source file
+    fn open_save_prompt(&mut self) {
+        let path = self
+            .cfg
+            .filepath
+            .as_ref()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default();
+        self.prompt = Some(Prompt::new(PromptKind::Save, path));
+    }
+
+    /// Act on the input submitted to the prompt
+    fn submit_prompt(&mut self, prompt: Prompt) {
+        match prompt.kind {
+            PromptKind::Save => self.save_to(prompt.input.trim()),
+            PromptKind::ConfirmQuit | PromptKind::ConfirmOverwrite => {}
+            PromptKind::Search => self.run_search(prompt.input),
+            PromptKind::GoTo => self.go_to(&prompt.input),
+            PromptKind::Copy => self.copy_range(prompt.input.trim()),
+        }
+    }
+
+    /// Save the changes to the path, asking for confirmation first if it would replace another file
+    fn save_to(&mut self, path: &str) {
+        if path.is_empty() {
+            self.status = Some(String::from(
+                "Cannot write back to STDIN; enter a path to save to",
+            ));
+            return;
+        }
+        let target = std::path::Path::new(path);
+        if target.exists() && !self.is_source(target) {
+            self.prompt = Some(Prompt::new(PromptKind::ConfirmOverwrite, path));
+            return;
+        }
+        self.write_to(path);
+    }
+
+    /// Save the changes to the path and report the outcome in the status line
+    fn write_to(&mut self, path: &str) {
+        self.status = Some(match self.save(std::path::Path::new(path)) {
+            Ok(n) => format!("Saved {} modified byte(s) to {}", n, path),
+            Err(e) => format!("Failed to save to {}: {}", path, e),
+        });
+    }
+
+    /// Select the byte at the address entered in the prompt and centre it in the view.
+    ///
+    /// Addresses include the starting offset, so that they match the offset column.
+    fn go_to(&mut self, input: &str) {
+        let start = self.data.start();
+        let current = start + self.selected;
+        let end = start + self.total_bytes;
+        let address = match goto::parse_address(input, current, end) {
+            Ok(address) => address,
+            Err(e) => {
+                self.status = Some(e);
+                return;
+            }
+        };
+
+        if address < start || address > end {
+            self.status = Some(format!(
+                "Address {:#x} is out of range ({:#x} - {:#x})",
+                address, start, end
+            ));
+            return;
+        }
+
+        // Go to the last byte for the address one past the end
+        self.selected = std::cmp::min(address - start, self.last());
+        self.centre_scroll_view();
+    }
+
+    /// Search for the query, and select the first match after the selection
+    fn run_search(&mut self, query: String) {
+        match Search::run(&query, &mut self.data, &self.edits) {
+            Ok(search) => {
+                self.status = Some(match (search.matches.len(), search.truncated) {
+                    (0, _) => format!("No matches for {}", query),
+                    (n, false) => format!("Found {} matches for {}", n, query),
+                    (n, true) => format!("Found more than {} matches for {}", n, query),
+                });
+                self.search = Some(search);
+                self.jump_to_next_match();
+            }
+            Err(e) => self.status = Some(e),
+        }
+    }
+
+    /// Select the next match of the search
+    fn jump_to_next_match(&mut self) {
+        if let Some(index) = self.search.as_ref().and_then(|s| s.next(self.selected)) {
+            self.select(index);
+        }
+    }
+
+    /// Select the previous match of the search
+    fn jump_to_previous_match(&mut self) {
+        if let Some(index) = self.search.as_ref().and_then(|s| s.previous(self.selected)) {
+            self.select(index);
+        }
+    }
+
+    /// Select the next byte that differs from the file to compare against
+    fn jump_to_next_difference(&mut self) {
+        let Some(data) = self.compare.as_mut() else {
+            self.status = Some(String::from("There is no file to compare against"));
+            return;
+        };
+        match compare::next_difference(&mut self.data, &self.edits, data, self.selected) {
+            Ok(Some(index)) => self.select(index),
+            Ok(None) => self.status = Some(String::from("The files are identical")),
+            Err(e) => self.status = Some(format!("Failed to compare the files: {}", e)),
+        }
+    }
+
+    /// Select the previous byte that differs from the file to compare against
+    fn jump_to_previous_difference(&mut self) {
+        let Some(data) = self.compare.as_mut() else {
+            self.status = Some(String::from("There is no file to compare against"));
+            return;
+        };
+        match compare::previous_difference(&mut self.data, &self.edits, data, self.selected) {
+            Ok(Some(index)) => self.select(index),
+            Ok(None) => self.status = Some(String::from("The files are identical")),
+            Err(e) => self.status = Some(format!("Failed to compare the files: {}", e)),
+        }
+    }
+
+    /// Start selecting a range from the selected byte, or stop if already selecting one
+    fn toggle_range_selection(&mut self) {
+        self.anchor = match self.anchor {
+            Some(_) => None,
+            None => Some(self.selected),
+        };
+    }
+
+    /// Copy the selected range (along with the unsaved edits) out in the encoding entered in the prompt.
+    ///
+    /// The bytes are copied to the system clipboard, unless the encoding is followed by a path to write to.
+    fn copy_range(&mut self, input: &str) {
+        let (name, path) = match input.split_once(char::is_whitespace) {
+            Some((name, path)) => (name, Some(path.trim())),
+            None => (input, None),
+        };
+        let Some(encoding) = Encoding::parse(name) else {
+            self.status = Some(format!("Unknown encoding: {}", name));
+            return;
+        };
+
+        // Read the selected range, which ends early if the data is shorter than the file compared against
+        let (first, last) = self.range();
+        let mut data = vec![0; last - first + 1];
+        let n = match self.data.read(first, &mut data) {
+            Ok(n) => n,
+            Err(e) => {
+                self.status = Some(format!("Failed to read the selection: {}", e));
+                return;
+            }
+        };
+        data.truncate(n);
+        self.edits.apply(first, &mut data);
+
+        let encoded = encoding.encode(&data);
+        let result = match path {
+            Some(path) => std::fs::write(path, encoded).map_err(|e| e.to_string()),
+            None => copy::to_clipboard(&encoded),
+        };
+        self.status = Some(match result {
+            Ok(()) => format!(
+                "Copied {} byte(s) as {} to {}",
+                n,
+                encoding.name(),
+                path.unwrap_or("the clipboard")
+            ),
+            Err(e) => format!("Failed to copy the selection: {}", e),
+        });
+    }
+
+    /// Collapse or expand the runs of identical rows
+    fn toggle_squeeze(&mut self) {
+        self.squeeze = !self.squeeze;
+        if self.compare.is_some() {
+            self.status = Some(String::from(
+                "Identical rows are not collapsed while comparing files",
+            ));
+        }
+        self.adjust_scroll_view();
+    }
+
+    /// Open the strings panel, listing the strings found in the data (or close it)
+    fn toggle_strings(&mut self) {
+        if self.strings.take().is_some() {
+            return;
+        }
+        // The data is scanned between frames, highlighting the first string at (or after) the
+        // selection without moving it
+        self.strings = Some(StringList::new(&self.edits, self.selected));
+        self.status = Some(String::from("Scanning for strings..."));
+    }
+
+    /// Switch the text column over to the next character encoding
+    fn cycle_encoding(&mut self) {
+        let encodings = hex_ray::Encoding::value_variants();
+        let i = encodings
+            .iter()
+            .position(|&e| e == self.encoding)
+            .unwrap_or(0);
+        self.encoding = encodings[(i + 1) % encodings.len()];
+        self.status = Some(format!("Text encoding: {}", self.encoding.name()));
+    }
+
+    /// Switch the byte-order of the values in the data inspector
+    fn toggle_endianness(&mut self) {
+        self.endianness = match self.endianness {
+            Endianness::Little => Endianness::Big,
+            Endianness::Big => Endianness::Little,
+        };
+    }
+
+    /// Revert the most recent edit and select the byte it affected
+    fn undo(&mut self) {
+        // The next hex digit starts over from the high nibble, even if the selection stays put
+        self.low_nibble = false;
+        match self.edits.undo() {
+            Some(index) => self.select(index),
+            None => self.status = Some(String::from("Nothing to undo")),
+        }
+    }
+
+    /// Reapply the most recently undone edit and select the byte it affected
+    fn redo(&mut self) {
+        self.low_nibble = false;
+        match self.edits.redo() {
+            Some(index) => self.select(index),
+            None => self.status = Some(String::from("Nothing to redo")),
+        }
+    }
+
+    /// Select the byte at the given index and bring it into view
+    fn select(&mut self, index: usize) {
+        self.selected = std::cmp::min(index, self.last());
+        self.adjust_scroll_view();
+    }
+
+    /// Exits the application, asking for confirmation first if there are unsaved changes
+    fn request_exit(&mut self) {
+        if self.edits.is_empty() {
+            self.exit();
+        } else {
+            self.prompt = Some(Prompt::new(PromptKind::ConfirmQuit, ""));
+        }
+    }
+
     /// Exits the application
     fn exit(&mut self) {
         self.exit = true;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_ray::utils::source::Buffered;
+
+    /// An app over the data, with the text column in UTF-8 and no rows paged in
+    fn app(data: &[u8]) -> App {
+        let mut app = App::default();
+        app.cfg.size = 4;
+        app.encoding = hex_ray::Encoding::Utf8;
+        app.load(Box::new(Buffered::from(data.to_vec())));
+        app
+    }
+
+    #[test]
+    fn should_write_characters_across_rows_that_are_not_paged_in() {
+        let mut app = app(b"abcdefgh");
+        app.select(3);
+        app.write_char('é');
+        assert_eq!(app.edits.iter().collect::<Vec<_>>(), [(3, 0xc3), (4, 0xa9)]);
+        assert_eq!(app.selected, 5);
+    }
+
+    #[test]
+    fn should_report_characters_that_do_not_fit() {
+        let mut app = app(b"abcd");
+        app.select(3);
+        app.write_char('é');
+        assert!(app.edits.is_empty());
+        assert!(app.status.is_some());
+    }
+}
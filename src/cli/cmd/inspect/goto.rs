@@ -0,0 +1,83 @@
+/// Parse the address to go to, as entered in the prompt.
+///
+/// The address can be one of the following:
+/// - An absolute address in decimal, or with a `0x`, `0o` or `0b` prefix (e.g. `4096` or `0x1000`)
+/// - An address relative to the current address (e.g. `+0x100` or `-512`)
+/// - An address relative to the end of the data (e.g. `end`, `end-0x10` or `$-16`)
+///
+/// `current` is the address of the selected byte and `end` is the address one past the last byte.
+pub fn parse_address(input: &str, current: usize, end: usize) -> Result<usize, String> {
+    let input: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+    let invalid = || format!("Invalid address: {}", input);
+
+    // Determine the base address for relative addresses
+    let (base, rest) = if let Some(rest) = input
+        .strip_prefix("end")
+        .or_else(|| input.strip_prefix('$'))
+    {
+        (Some(end), rest)
+    } else if input.starts_with(['+', '-']) {
+        (Some(current), input.as_str())
+    } else {
+        (None, input.as_str())
+    };
+
+    match base {
+        None => parse_number(rest).ok_or_else(invalid),
+        Some(base) if rest.is_empty() => Ok(base),
+        Some(base) => {
+            let (sign, number) = rest.split_at(1);
+            let n = parse_number(number).ok_or_else(invalid)?;
+            match sign {
+                "+" => base.checked_add(n).ok_or_else(invalid),
+                "-" => base
+                    .checked_sub(n)
+                    .ok_or_else(|| format!("Address is before the start: {}", input)),
+                _ => Err(invalid()),
+            }
+        }
+    }
+}
+
+/// Parse a number in decimal, or in hex, octal or binary with the corresponding prefix
+fn parse_number(s: &str) -> Option<usize> {
+    let lower = s.to_ascii_lowercase();
+    let (digits, radix) = if let Some(digits) = lower.strip_prefix("0x") {
+        (digits, 16)
+    } else if let Some(digits) = lower.strip_prefix("0o") {
+        (digits, 8)
+    } else if let Some(digits) = lower.strip_prefix("0b") {
+        (digits, 2)
+    } else {
+        (lower.as_str(), 10)
+    };
+    usize::from_str_radix(&digits.replace('_', ""), radix).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_absolute_addresses() {
+        assert_eq!(parse_address("4096", 0, 0), Ok(4096));
+        assert_eq!(parse_address("0x1000", 0, 0), Ok(4096));
+        assert_eq!(parse_address("0o10", 0, 0), Ok(8));
+        assert_eq!(parse_address("0b101", 0, 0), Ok(5));
+        assert!(parse_address("0xzz", 0, 0).is_err());
+    }
+
+    #[test]
+    fn should_parse_relative_addresses() {
+        assert_eq!(parse_address("+0x100", 16, 0), Ok(272));
+        assert_eq!(parse_address("-8", 16, 0), Ok(8));
+        assert!(parse_address("-32", 16, 0).is_err());
+    }
+
+    #[test]
+    fn should_parse_addresses_relative_to_the_end() {
+        assert_eq!(parse_address("end", 0, 100), Ok(100));
+        assert_eq!(parse_address("end - 0x10", 0, 100), Ok(84));
+        assert_eq!(parse_address("$-1", 0, 100), Ok(99));
+    }
+}
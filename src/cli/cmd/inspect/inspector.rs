@@ -0,0 +1,284 @@
+// Library
+use hex_ray::utils::format::Endianness;
+
+/// The maximum number of bytes (beginning at the selected byte) that are interpreted
+pub const MAX_LEN: usize = 16;
+
+/// The seconds between the start of the FILETIME epoch (1601-01-01) and the Unix epoch (1970-01-01)
+const FILETIME_EPOCH_OFFSET: i64 = 11_644_473_600;
+
+/// The value of the bytes interpreted as a given type, if they are valid as such
+pub type Interpretation = (&'static str, Option<String>);
+
+/// Interpret the bytes (beginning at the selected byte) as each of the types in the data inspector.
+///
+/// Multi-byte values are decoded in the given byte-order. A value is `None` if there are
+/// not enough bytes for it, or if the bytes are not a valid encoding of the type.
+pub fn interpret(bytes: &[u8], endianness: Endianness) -> Vec<Interpretation> {
+    let int = |n: usize, signed: bool| {
+        let data = read::<16>(bytes, n, endianness)?;
+        let value = u128::from_le_bytes(data);
+        Some(match signed {
+            true => {
+                let shift = 128 - n * 8;
+                (((value << shift) as i128) >> shift).to_string()
+            }
+            false => value.to_string(),
+        })
+    };
+    let float32 = read::<4>(bytes, 4, endianness).map(|b| format_float(f32::from_le_bytes(b)));
+    let float64 = read::<8>(bytes, 8, endianness).map(|b| format_float(f64::from_le_bytes(b)));
+
+    vec![
+        ("i8", int(1, true)),
+        ("u8", int(1, false)),
+        ("i16", int(2, true)),
+        ("u16", int(2, false)),
+        ("i32", int(4, true)),
+        ("u32", int(4, false)),
+        ("i64", int(8, true)),
+        ("u64", int(8, false)),
+        ("f32", float32),
+        ("f64", float64),
+        ("ULEB128", uleb128(bytes)),
+        ("SLEB128", sleb128(bytes)),
+        ("UTF-8", utf8(bytes)),
+        ("UTF-16", utf16(bytes, endianness)),
+        ("Unix time", unix_time(bytes, endianness)),
+        ("DOS date/time", dos_date_time(bytes, endianness)),
+        ("FILETIME", filetime(bytes, endianness)),
+        ("GUID", guid(bytes, endianness)),
+    ]
+}
+
+/// Read the first `n` bytes into a little-endian array of `N` bytes, padded with zeros
+fn read<const N: usize>(bytes: &[u8], n: usize, endianness: Endianness) -> Option<[u8; N]> {
+    let bytes = bytes.get(..n)?;
+    let mut data = [0; N];
+    data[..n].copy_from_slice(bytes);
+    if endianness == Endianness::Big {
+        data[..n].reverse();
+    }
+    Some(data)
+}
+
+/// Format the floating point number, switching to scientific notation for very large or small values
+fn format_float<T>(value: T) -> String
+where
+    T: std::fmt::Display + std::fmt::LowerExp + Into<f64> + Copy,
+{
+    let abs = value.into().abs();
+    if abs == 0.0 || !abs.is_finite() || (1e-4..1e15).contains(&abs) {
+        format!("{}", value)
+    } else {
+        format!("{:e}", value)
+    }
+}
+
+/// Decode the variable-length unsigned integer, along with the number of bytes it spans
+fn uleb128(bytes: &[u8]) -> Option<String> {
+    let mut value: u64 = 0;
+    for (i, byte) in bytes.iter().enumerate().take(10) {
+        let bits = (*byte & 0x7f) as u64;
+        if i == 9 && bits > 1 {
+            return None; // Overflows 64 bits
+        }
+        value |= bits << (i * 7);
+        if byte & 0x80 == 0 {
+            return Some(format!("{} ({} bytes)", value, i + 1));
+        }
+    }
+    None
+}
+
+/// Decode the variable-length signed integer, along with the number of bytes it spans
+fn sleb128(bytes: &[u8]) -> Option<String> {
+    let mut value: i64 = 0;
+    for (i, byte) in bytes.iter().enumerate().take(10) {
+        value |= ((*byte & 0x7f) as i64) << (i * 7);
+        if byte & 0x80 == 0 {
+            // Sign-extend from the last bit that was read
+            let shift = 64usize.saturating_sub((i + 1) * 7);
+            return Some(format!("{} ({} bytes)", (value << shift) >> shift, i + 1));
+        }
+    }
+    None
+}
+
+/// Decode the UTF-8 encoded character
+fn utf8(bytes: &[u8]) -> Option<String> {
+    let len = match bytes.first()? {
+        0x00..=0x7f => 1,
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf7 => 4,
+        _ => return None,
+    };
+    let c = std::str::from_utf8(bytes.get(..len)?)
+        .ok()?
+        .chars()
+        .next()?;
+    Some(format_char(c))
+}
+
+/// Decode the UTF-16 encoded character, which may be made up of a surrogate pair
+fn utf16(bytes: &[u8], endianness: Endianness) -> Option<String> {
+    let units = bytes
+        .chunks_exact(2)
+        .take(2)
+        .filter_map(|b| read::<2>(b, 2, endianness).map(u16::from_le_bytes));
+    let c = char::decode_utf16(units).next()?.ok()?;
+    Some(format_char(c))
+}
+
+/// Format the character along with its code point, escaping control characters
+fn format_char(c: char) -> String {
+    match c.is_control() {
+        true => format!("U+{:04X}", c as u32),
+        false => format!("U+{:04X} '{}'", c as u32, c),
+    }
+}
+
+/// Decode the 32-bit count of seconds since the Unix epoch
+fn unix_time(bytes: &[u8], endianness: Endianness) -> Option<String> {
+    let seconds = i32::from_le_bytes(read(bytes, 4, endianness)?);
+    Some(format_date_time(seconds as i64))
+}
+
+/// Decode the MS-DOS (FAT) time, followed by the date
+fn dos_date_time(bytes: &[u8], endianness: Endianness) -> Option<String> {
+    let time = u16::from_le_bytes(read(bytes, 2, endianness)?);
+    let date = u16::from_le_bytes(read(bytes.get(2..)?, 2, endianness)?);
+
+    let (year, month, day) = (1980 + (date >> 9), (date >> 5) & 0x0f, date & 0x1f);
+    let (hours, minutes, seconds) = (time >> 11, (time >> 5) & 0x3f, (time & 0x1f) * 2);
+    if !(1..=12).contains(&month) || day == 0 || hours > 23 || minutes > 59 || seconds > 59 {
+        return None;
+    }
+    Some(format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year, month, day, hours, minutes, seconds
+    ))
+}
+
+/// Decode the Windows FILETIME; the count of 100-nanosecond intervals since 1601-01-01
+fn filetime(bytes: &[u8], endianness: Endianness) -> Option<String> {
+    let intervals = u64::from_le_bytes(read(bytes, 8, endianness)?);
+    let seconds = (intervals / 10_000_000) as i64 - FILETIME_EPOCH_OFFSET;
+    Some(format_date_time(seconds))
+}
+
+/// Decode the GUID. In little-endian, the first three fields are stored in little-endian
+/// byte-order (as on Windows), while in big-endian all of the bytes are in order (as per RFC 4122)
+fn guid(bytes: &[u8], endianness: Endianness) -> Option<String> {
+    let bytes = bytes.get(..16)?;
+    let data1 = u32::from_le_bytes(read(bytes, 4, endianness)?);
+    let data2 = u16::from_le_bytes(read(&bytes[4..], 2, endianness)?);
+    let data3 = u16::from_le_bytes(read(&bytes[6..], 2, endianness)?);
+    let data4: String = bytes[8..].iter().map(|b| format!("{:02x}", b)).collect();
+    Some(format!(
+        "{:08x}-{:04x}-{:04x}-{}-{}",
+        data1,
+        data2,
+        data3,
+        &data4[..4],
+        &data4[4..]
+    ))
+}
+
+/// Format the seconds since the Unix epoch as a UTC date and time
+fn format_date_time(seconds: i64) -> String {
+    let (days, time) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+
+    // Convert the days since the epoch to a civil date (see http://howardhinnant.github.io/date_algorithms.html)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the interpretation of the bytes as the given type
+    fn value(bytes: &[u8], endianness: Endianness, label: &str) -> Option<String> {
+        interpret(bytes, endianness)
+            .into_iter()
+            .find(|(l, _)| *l == label)
+            .and_then(|(_, value)| value)
+    }
+
+    #[test]
+    fn should_interpret_integers_in_either_byte_order() {
+        let bytes = [0xfe, 0xff, 0x00, 0x01];
+        assert_eq!(value(&bytes, Endianness::Little, "i16").unwrap(), "-2");
+        assert_eq!(value(&bytes, Endianness::Big, "u16").unwrap(), "65279");
+        assert_eq!(
+            value(&bytes, Endianness::Little, "u32").unwrap(),
+            "16842750"
+        );
+        assert_eq!(value(&bytes, Endianness::Little, "i64"), None);
+    }
+
+    #[test]
+    fn should_interpret_variable_length_integers() {
+        assert_eq!(uleb128(&[0xe5, 0x8e, 0x26]).unwrap(), "624485 (3 bytes)");
+        assert_eq!(sleb128(&[0xc0, 0xbb, 0x78]).unwrap(), "-123456 (3 bytes)");
+        assert_eq!(uleb128(&[0x80, 0x80]), None);
+    }
+
+    #[test]
+    fn should_interpret_characters() {
+        assert_eq!(utf8("é".as_bytes()).unwrap(), "U+00E9 'é'");
+        assert_eq!(utf8(&[0xc3]), None);
+        let pair = [0x3d, 0xd8, 0x00, 0xde]; // U+1F600 as a surrogate pair
+        assert_eq!(utf16(&pair, Endianness::Little).unwrap(), "U+1F600 '😀'");
+    }
+
+    #[test]
+    fn should_interpret_dates_and_times() {
+        let le = Endianness::Little;
+        assert_eq!(
+            unix_time(&1_700_000_000u32.to_le_bytes(), le).unwrap(),
+            "2023-11-14 22:13:20 UTC"
+        );
+        assert_eq!(
+            filetime(&116_444_736_000_000_000u64.to_le_bytes(), le).unwrap(),
+            "1970-01-01 00:00:00 UTC"
+        );
+        // 2000-01-02 03:04:06
+        let (time, date) = ((3u16 << 11) | (4 << 5) | 3, (20u16 << 9) | (1 << 5) | 2);
+        let bytes = [time.to_le_bytes(), date.to_le_bytes()].concat();
+        assert_eq!(dos_date_time(&bytes, le).unwrap(), "2000-01-02 03:04:06");
+    }
+
+    #[test]
+    fn should_interpret_guids() {
+        let bytes: Vec<u8> = (0..16).collect();
+        assert_eq!(
+            guid(&bytes, Endianness::Little).unwrap(),
+            "03020100-0504-0706-0809-0a0b0c0d0e0f"
+        );
+        assert_eq!(
+            guid(&bytes, Endianness::Big).unwrap(),
+            "00010203-0405-0607-0809-0a0b0c0d0e0f"
+        );
+    }
+}
@@ -0,0 +1,311 @@
+// Library
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+
+use crate::cli::config::Keys;
+
+/// The commands that can be bound to keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Edit,
+    Save,
+    Undo,
+    Redo,
+    Search,
+    GoTo,
+    NextMatch,
+    PreviousMatch,
+    NextDifference,
+    PreviousDifference,
+    Endianness,
+    Encoding,
+    SelectRange,
+    Copy,
+    Squeeze,
+    Strings,
+    Quit,
+}
+
+/// The name of each action in the config file, along with the keys bound to it by default
+const DEFAULT_BINDINGS: [(Action, &str, &[&str]); 25] = [
+    (Action::Up, "up", &["up"]),
+    (Action::Down, "down", &["down"]),
+    (Action::Left, "left", &["left"]),
+    (Action::Right, "right", &["right"]),
+    (Action::Home, "home", &["home"]),
+    (Action::End, "end", &["end"]),
+    (Action::PageUp, "page-up", &["pgup"]),
+    (Action::PageDown, "page-down", &["pgdn"]),
+    (Action::Edit, "edit", &["i", "insert"]),
+    (Action::Save, "save", &["w"]),
+    (Action::Undo, "undo", &["u"]),
+    (Action::Redo, "redo", &["U"]),
+    (Action::Search, "search", &["/"]),
+    (Action::GoTo, "go-to", &["g"]),
+    (Action::NextMatch, "next-match", &["n"]),
+    (Action::PreviousMatch, "previous-match", &["N"]),
+    (Action::NextDifference, "next-difference", &["d"]),
+    (Action::PreviousDifference, "previous-difference", &["D"]),
+    (Action::Endianness, "endianness", &["e"]),
+    (Action::Encoding, "encoding", &["t"]),
+    (Action::SelectRange, "select-range", &["v"]),
+    (Action::Copy, "copy", &["y"]),
+    (Action::Squeeze, "squeeze", &["z"]),
+    (Action::Strings, "strings", &["s"]),
+    (Action::Quit, "quit", &["q"]),
+];
+
+impl Action {
+    /// Returns true if the action moves the selection (which selects a range while holding shift)
+    pub fn is_movement(self) -> bool {
+        matches!(
+            self,
+            Action::Up
+                | Action::Down
+                | Action::Left
+                | Action::Right
+                | Action::Home
+                | Action::End
+                | Action::PageUp
+                | Action::PageDown
+        )
+    }
+}
+
+/// A key along with the modifiers held down
+type Key = (KeyCode, KeyModifiers);
+
+/// The keys bound to each action
+#[derive(Debug, Clone)]
+pub struct KeyBindings {
+    bindings: HashMap<Key, Action>,  // The action of each bound key
+    labels: HashMap<Action, String>, // The first key bound to each action, as shown in the help line
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let mut bindings = Self {
+            bindings: HashMap::new(),
+            labels: HashMap::new(),
+        };
+        for (action, _, keys) in DEFAULT_BINDINGS {
+            for key in keys {
+                let key = parse_key(key).expect("the default key bindings are valid");
+                bindings.bind(key, action);
+            }
+        }
+        bindings
+    }
+}
+
+impl KeyBindings {
+    /// The default key bindings, with the keys of the actions in the config file replaced
+    pub fn with_config(config: &HashMap<String, Keys>) -> Result<Self, String> {
+        let mut bindings = Self::default();
+        for (name, keys) in config {
+            let Some((action, _, _)) = DEFAULT_BINDINGS.iter().find(|(_, n, _)| n == name) else {
+                return Err(format!("Unknown action {:?} in the [keys] section", name));
+            };
+
+            bindings.bindings.retain(|_, a| a != action);
+            bindings.labels.remove(action);
+            for key in keys.to_vec() {
+                let parsed = parse_key(&key)
+                    .ok_or_else(|| format!("Invalid key {:?} for the action {:?}", key, name))?;
+                bindings.bind(parsed, *action);
+            }
+        }
+        Ok(bindings)
+    }
+
+    /// Returns the action bound to the key event, if any.
+    ///
+    /// The movement keys also move the selection while holding shift or control.
+    pub fn action(&self, event: KeyEvent) -> Option<Action> {
+        let key = normalize((event.code, event.modifiers));
+        if let Some(action) = self.bindings.get(&key) {
+            return Some(*action);
+        }
+        let unmodified = (key.0, key.1 - KeyModifiers::SHIFT - KeyModifiers::CONTROL);
+        self.bindings
+            .get(&unmodified)
+            .copied()
+            .filter(|action| action.is_movement())
+    }
+
+    /// The first key bound to the action, as shown in the help line
+    pub fn label(&self, action: Action) -> &str {
+        self.labels.get(&action).map_or("-", String::as_str)
+    }
+
+    /// Bind the key to the action
+    fn bind(&mut self, key: Key, action: Action) {
+        self.labels.entry(action).or_insert_with(|| format_key(key));
+        self.bindings.insert(key, action);
+    }
+}
+
+/// Parse a key, optionally prefixed with modifiers (e.g. `q`, `ctrl+f`, `pgdn` or `shift+tab`)
+pub fn parse_key(s: &str) -> Option<Key> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut parts: Vec<&str> = s.split('+').collect();
+    // A `+` on its own (or at the end, as in `ctrl++`) is the plus key
+    let name = match parts.pop()? {
+        "" if parts.last() == Some(&"") => {
+            parts.pop();
+            "+"
+        }
+        name => name,
+    };
+    for modifier in parts {
+        modifiers |= match modifier.to_lowercase().as_str() {
+            "ctrl" | "control" => KeyModifiers::CONTROL,
+            "alt" => KeyModifiers::ALT,
+            "shift" => KeyModifiers::SHIFT,
+            _ => return None,
+        };
+    }
+
+    let mut chars = name.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(c), None) => KeyCode::Char(c),
+        _ => match name.to_lowercase().as_str() {
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pgup" | "pageup" | "page-up" => KeyCode::PageUp,
+            "pgdn" | "pagedown" | "page-down" => KeyCode::PageDown,
+            "insert" | "ins" => KeyCode::Insert,
+            "delete" | "del" => KeyCode::Delete,
+            "backspace" => KeyCode::Backspace,
+            "enter" | "return" => KeyCode::Enter,
+            "tab" => KeyCode::Tab,
+            "space" => KeyCode::Char(' '),
+            "esc" | "escape" => KeyCode::Esc,
+            f => KeyCode::F(f.strip_prefix('f')?.parse().ok()?),
+        },
+    };
+    Some(normalize((code, modifiers)))
+}
+
+/// Characters carry their own case, so shift is left out for them (`shift+u` is `U`)
+fn normalize((code, modifiers): Key) -> Key {
+    match code {
+        KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => (
+            KeyCode::Char(c.to_ascii_uppercase()),
+            modifiers - KeyModifiers::SHIFT,
+        ),
+        _ => (code, modifiers),
+    }
+}
+
+/// Format a key for the help line
+fn format_key((code, modifiers): Key) -> String {
+    let name = match code {
+        KeyCode::Char(' ') => String::from("space"),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Up => String::from("↑"),
+        KeyCode::Down => String::from("↓"),
+        KeyCode::Left => String::from("←"),
+        KeyCode::Right => String::from("→"),
+        KeyCode::PageUp => String::from("pgup"),
+        KeyCode::PageDown => String::from("pgdn"),
+        KeyCode::F(n) => format!("f{}", n),
+        code => code.to_string().to_lowercase(),
+    };
+    let mut prefix = String::new();
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        prefix.push_str("ctrl+");
+    }
+    if modifiers.contains(KeyModifiers::ALT) {
+        prefix.push_str("alt+");
+    }
+    if modifiers.contains(KeyModifiers::SHIFT) {
+        prefix.push_str("shift+");
+    }
+    prefix + &name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn should_parse_keys() {
+        assert_eq!(
+            parse_key("q"),
+            Some((KeyCode::Char('q'), KeyModifiers::NONE))
+        );
+        assert_eq!(
+            parse_key("shift+u"),
+            Some((KeyCode::Char('U'), KeyModifiers::NONE))
+        );
+        assert_eq!(
+            parse_key("ctrl+f"),
+            Some((KeyCode::Char('f'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            parse_key("ctrl++"),
+            Some((KeyCode::Char('+'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            parse_key("PgDn"),
+            Some((KeyCode::PageDown, KeyModifiers::NONE))
+        );
+        assert_eq!(parse_key("f5"), Some((KeyCode::F(5), KeyModifiers::NONE)));
+        assert_eq!(parse_key("hyper+x"), None);
+        assert_eq!(parse_key("nope"), None);
+    }
+
+    #[test]
+    fn should_remap_the_keys_of_actions() {
+        let config = HashMap::from([
+            (
+                String::from("down"),
+                Keys::Many(vec![String::from("j"), String::from("down")]),
+            ),
+            (String::from("quit"), Keys::One(String::from("ctrl+q"))),
+        ]);
+        let keys = KeyBindings::with_config(&config).unwrap();
+
+        assert_eq!(
+            keys.action(event(KeyCode::Char('j'), KeyModifiers::NONE)),
+            Some(Action::Down)
+        );
+        assert_eq!(
+            keys.action(event(KeyCode::Down, KeyModifiers::SHIFT)),
+            Some(Action::Down)
+        );
+        assert_eq!(
+            keys.action(event(KeyCode::Char('q'), KeyModifiers::NONE)),
+            None
+        );
+        assert_eq!(
+            keys.action(event(KeyCode::Char('q'), KeyModifiers::CONTROL)),
+            Some(Action::Quit)
+        );
+        assert_eq!(
+            keys.action(event(KeyCode::Char('U'), KeyModifiers::SHIFT)),
+            Some(Action::Redo)
+        );
+        assert_eq!(keys.label(Action::Down), "j");
+
+        let config = HashMap::from([(String::from("fly"), Keys::One(String::from("f")))]);
+        assert!(KeyBindings::with_config(&config).is_err());
+    }
+}
@@ -1,30 +1,79 @@
 // Library
 mod app;
+mod compare;
+mod copy;
+mod edits;
 mod events;
+mod goto;
+mod inspector;
+mod keys;
+mod pager;
+mod prompt;
 mod row;
+mod search;
+mod squeeze;
+mod strings;
 mod ui;
 
 // Library
 use super::View;
-use crate::utils::helpers;
+use crate::cli::config;
 use app::App;
+use clap::Parser;
+use hex_ray::utils::source;
+use hex_ray::Error;
+use keys::KeyBindings;
 
-impl View {
+// ---------------
+// INSPECT COMMAND
+// ---------------
+
+#[derive(Parser, Clone, Debug, Default)]
+#[command(version, about)]
+pub struct Inspect {
+    #[command(flatten)]
+    pub view: View,
+
+    /// Path to a second file to compare against, side by side.
+    ///
+    /// The bytes at the same positions in both files are compared, and the differences highlighted
+    pub compare: Option<std::path::PathBuf>,
+}
+
+impl Inspect {
     /// View the hex-dump in an interactive session
-    pub fn execute_interactively(self) -> Result<(), Box<dyn std::error::Error>> {
-        self.init(); // Initialize the configuration
+    pub fn execute(mut self) -> hex_ray::Result<()> {
+        self.view.init()?; // Initialize the configuration
 
-        // Get the reader and starting offset
-        let (reader, offset) = helpers::get_reader_and_offset(self.filepath.as_ref(), self.offset)?;
+        // Open the sources of data; memory-mapped for regular files
+        let data = source::open(
+            self.view.filepath.as_ref(),
+            self.view.offset,
+            self.view.limit,
+        )?;
+        let keys = KeyBindings::with_config(&config::get().keys).map_err(Error::InvalidConfig)?;
+        let compare = match &self.compare {
+            Some(path) => Some(source::open(Some(path), self.view.offset, self.view.limit)?),
+            None => None,
+        };
 
         // Initialize the terminal
-        let mut terminal = ratatui::init();
-        terminal.clear()?;
+        let mut terminal = ratatui::try_init().map_err(Error::Terminal)?;
+        let size = match terminal.clear().and_then(|_| terminal.size()) {
+            Ok(size) => size,
+            Err(e) => {
+                ratatui::restore();
+                return Err(Error::Terminal(e));
+            }
+        };
 
         // Initialize the application
-        let size = terminal.size()?;
-        let mut app = App::new(self, size.height);
-        app.parse(reader, offset)?;
+        let mut app = App::new(self.view, size.height);
+        app.keys = keys;
+        app.load(data);
+        if let Some(compare) = compare {
+            app.load_compare(compare, self.compare);
+        }
 
         // Run the application
         let app_result = app.run(&mut terminal);
@@ -0,0 +1,221 @@
+// Library
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ops::Range;
+
+use super::row::Row;
+use hex_ray::utils::source::{Buffered, Source};
+
+/// The minimum number of rows to keep in the cache
+const MIN_CAPACITY: usize = 256;
+
+/// The number of bytes to copy out at a time
+const COPY_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Loads rows of data on-demand from a source.
+///
+/// Only the rows that have been requested recently are kept in memory. Once the cache grows
+/// beyond its capacity, the rows furthest from the most recently requested window are evicted.
+pub struct Pager {
+    source: Box<dyn Source>,    // The source of data
+    row_size: usize,            // The number of bytes in each row
+    capacity: usize,            // The maximum number of rows to keep in the cache
+    cache: HashMap<usize, Row>, // The cached rows keyed by their row index
+}
+
+impl Pager {
+    /// Instantiate a new Pager for the data of the `source`
+    pub fn new(source: Box<dyn Source>, row_size: usize) -> Self {
+        Self {
+            source,
+            row_size,
+            capacity: MIN_CAPACITY,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Set the maximum number of rows to keep in memory
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = std::cmp::max(capacity, MIN_CAPACITY);
+        self
+    }
+
+    /// The total number of bytes available
+    pub fn len(&self) -> usize {
+        self.source.len()
+    }
+
+    /// The position in the source at which the data begins
+    pub fn start(&self) -> usize {
+        self.source.start()
+    }
+
+    /// The number of bytes in each row
+    pub fn row_size(&self) -> usize {
+        self.row_size
+    }
+
+    /// The total number of rows available
+    pub fn rows(&self) -> usize {
+        self.len().div_ceil(self.row_size)
+    }
+
+    /// Returns the row at the given index, if it has been loaded
+    pub fn get(&self, index: usize) -> Option<&Row> {
+        self.cache.get(&index)
+    }
+
+    /// Ensure that all the given rows are loaded, evicting the rows furthest from them if needed
+    pub fn load(&mut self, rows: impl IntoIterator<Item = usize>) -> std::io::Result<()> {
+        let mut indices: Vec<usize> = rows.into_iter().filter(|&i| i < self.rows()).collect();
+        indices.sort_unstable();
+        indices.dedup();
+
+        for &index in &indices {
+            if self.cache.contains_key(&index) {
+                continue;
+            }
+
+            // Read the span of bytes that make up this row from the source
+            let position = index * self.row_size;
+            let offset = self.start() + position;
+            let row = Row::new(
+                &self.source.bytes(position..position + self.row_size)?,
+                offset,
+            );
+
+            self.cache.insert(index, row);
+        }
+
+        self.evict(&indices);
+        Ok(())
+    }
+
+    /// Returns the bytes in the given range of indices, bypassing the cache.
+    ///
+    /// Memory-mapped data is borrowed as it is, without copying it. The range is clamped
+    /// to the end of the data.
+    pub fn bytes(&mut self, range: Range<usize>) -> std::io::Result<Cow<'_, [u8]>> {
+        self.source.bytes(range)
+    }
+
+    /// Read the bytes beginning at the given index into the buffer, bypassing the cache.
+    ///
+    /// Returns the number of bytes read, which is less than the size of the buffer
+    /// only if the end of the data was reached.
+    pub fn read(&mut self, index: usize, buffer: &mut [u8]) -> std::io::Result<usize> {
+        let bytes = self
+            .source
+            .bytes(index..index.saturating_add(buffer.len()))?;
+        buffer[..bytes.len()].copy_from_slice(&bytes);
+        Ok(bytes.len())
+    }
+
+    /// Copy all the available bytes from the source into the writer
+    pub fn copy_to<W>(&mut self, writer: &mut W) -> std::io::Result<u64>
+    where
+        W: std::io::Write,
+    {
+        let mut position = 0;
+        while position < self.len() {
+            let bytes = self.source.bytes(position..position + COPY_CHUNK_SIZE)?;
+            writer.write_all(&bytes)?;
+            position += bytes.len();
+        }
+        Ok(position as u64)
+    }
+
+    /// Drop the rows furthest away from the given (sorted) rows until the cache is within capacity
+    fn evict(&mut self, requested: &[usize]) {
+        if self.cache.len() <= self.capacity {
+            return;
+        }
+
+        // The distance of a row from the nearest of the requested rows
+        let distance = |index: usize| {
+            let i = requested.partition_point(|&r| r < index);
+            let above = i.checked_sub(1).map(|i| index - requested[i]);
+            let below = requested.get(i).map(|&r| r - index);
+            match (above, below) {
+                (Some(a), Some(b)) => std::cmp::min(a, b),
+                (a, b) => a.or(b).unwrap_or(0),
+            }
+        };
+
+        let mut indices: Vec<usize> = self.cache.keys().copied().collect();
+        indices.sort_by_key(|&index| std::cmp::Reverse(distance(index)));
+
+        let excess = self.cache.len() - self.capacity;
+        for index in indices.into_iter().take(excess) {
+            self.cache.remove(&index);
+        }
+    }
+}
+
+impl Default for Pager {
+    fn default() -> Self {
+        Self::new(Box::new(Buffered::from(Vec::new())), 16)
+    }
+}
+
+impl std::fmt::Debug for Pager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Pager")
+            .field("start", &self.start())
+            .field("len", &self.len())
+            .field("row_size", &self.row_size)
+            .field("capacity", &self.capacity)
+            .field("cached", &self.cache.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pager(len: usize, row_size: usize) -> Pager {
+        let data: Vec<u8> = (0..len).map(|i| i as u8).collect();
+        Pager::new(Box::new(Buffered::from(data)), row_size)
+    }
+
+    #[test]
+    fn should_load_only_the_requested_rows() {
+        let mut pager = pager(100, 16);
+        pager.load(2..4).unwrap();
+        assert!(pager.get(1).is_none());
+        assert_eq!(pager.get(2).unwrap().data, (32..48).collect::<Vec<u8>>());
+        assert_eq!(pager.get(3).unwrap().offset, 48);
+        assert!(pager.get(4).is_none());
+    }
+
+    #[test]
+    fn should_load_a_partial_last_row() {
+        let mut pager = pager(100, 16);
+        pager.load(0..100).unwrap();
+        assert_eq!(pager.rows(), 7);
+        assert_eq!(pager.get(6).unwrap().data, (96..100).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn should_evict_distant_rows() {
+        let mut pager = pager(16 * 1024, 16).with_capacity(0);
+        pager.load(0..MIN_CAPACITY).unwrap();
+        pager.load(512..522).unwrap();
+        assert_eq!(pager.cache.len(), MIN_CAPACITY);
+        assert!(pager.get(0).is_none());
+        assert!(pager.get(512).is_some());
+        assert!(pager.get(MIN_CAPACITY - 1).is_some());
+    }
+
+    #[test]
+    fn should_keep_rows_near_any_of_the_requested_rows() {
+        let mut pager = pager(16 * 1024, 16).with_capacity(0);
+        pager.load(0..MIN_CAPACITY).unwrap();
+        pager.load([0, 1, 900]).unwrap();
+        assert_eq!(pager.cache.len(), MIN_CAPACITY);
+        assert!(pager.get(0).is_some());
+        assert!(pager.get(900).is_some());
+        assert!(pager.get(MIN_CAPACITY - 1).is_none());
+    }
+}
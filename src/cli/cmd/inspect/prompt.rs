@@ -0,0 +1,55 @@
+/// The purpose of the input prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptKind {
+    /// The path to save the changes to
+    Save,
+    /// Whether to quit without saving the changes
+    ConfirmQuit,
+    /// Whether to save over the existing file at the path entered before
+    ConfirmOverwrite,
+    /// The bytes or text to search for
+    Search,
+    /// The address to go to
+    GoTo,
+    /// The encoding (and optionally the path) to copy the selected range to
+    Copy,
+}
+
+/// An input line shown in place of the help line
+#[derive(Debug)]
+pub struct Prompt {
+    pub kind: PromptKind, // What the input will be used for
+    pub input: String,    // The text entered so far
+}
+
+impl Prompt {
+    /// Instantiate a new Prompt with the initial input
+    pub fn new(kind: PromptKind, input: impl Into<String>) -> Self {
+        Self {
+            kind,
+            input: input.into(),
+        }
+    }
+
+    /// Returns true if the prompt only asks for a yes or no; its input is not shown
+    pub fn is_confirmation(&self) -> bool {
+        matches!(
+            self.kind,
+            PromptKind::ConfirmQuit | PromptKind::ConfirmOverwrite
+        )
+    }
+
+    /// The label shown before the input
+    pub fn label(&self) -> &'static str {
+        match self.kind {
+            PromptKind::Save => "Save to: ",
+            PromptKind::ConfirmQuit => "There are unsaved changes. Quit anyway? (y/N) ",
+            PromptKind::ConfirmOverwrite => "The file already exists. Overwrite it? (y/N) ",
+            PromptKind::Search => "Search (hex, \"ascii\" or u\"utf-16\"): ",
+            PromptKind::GoTo => "Go to (0x100, +16, -0o20, end-0x10): ",
+            PromptKind::Copy => {
+                "Copy as hex, c, base64 or raw (followed by a path to write to a file): "
+            }
+        }
+    }
+}
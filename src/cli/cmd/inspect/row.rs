@@ -1,4 +1,4 @@
-use crate::utils::format::Format;
+use hex_ray::utils::format::Radix;
 
 #[derive(Debug)]
 pub struct Row {
@@ -15,17 +15,10 @@ impl Row {
         }
     }
 
-    /// Return a formatted offset string
-    pub fn format_offset(&self) -> String {
-        let res = Format::Octal.format(self.offset as u8);
-        if res.len() > 8 {
-            return format!("{:0>8}", res);
-        }
-
-        let mut padding = String::from("");
-        for _ in 0..(8 - res.len()) {
-            padding.push_str(&"·");
-        }
+    /// Return a formatted offset string in the given radix, padded out to the given width
+    pub fn format_offset(&self, radix: Radix, width: usize) -> String {
+        let res = radix.format(self.offset as u64);
+        let padding = "·".repeat(width.saturating_sub(res.len()));
         format!("{}{}", padding, res)
     }
 }
@@ -0,0 +1,223 @@
+// Library
+use super::edits::Edits;
+use super::pager::Pager;
+
+/// The maximum number of matches to collect
+const MAX_MATCHES: usize = 100_000;
+
+/// The number of bytes to scan at a time
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// A sequence of bytes to look for, where `None` matches any byte
+type Pattern = Vec<Option<u8>>;
+
+/// The results of searching the data for a query
+#[derive(Debug, Default)]
+pub struct Search {
+    pub matches: Vec<usize>, // The (sorted) indices at which the matches begin
+    pub len: usize,          // The number of bytes in each match
+    pub truncated: bool,     // Whether there were more matches than were collected
+}
+
+impl Search {
+    /// Search all the data (along with the unsaved edits) for the query.
+    ///
+    /// The query can be one of the following:
+    /// - A sequence of hex bytes where `??` matches any byte (e.g. `de ad ?? ef`)
+    /// - A quoted ASCII string (e.g. `"hello"`), or any other text that is not valid hex
+    /// - A UTF-16 string prefixed with `u` (e.g. `u"hello"`), in either byte-order
+    pub fn run(query: &str, data: &mut Pager, edits: &Edits) -> Result<Self, String> {
+        let patterns = parse(query)?;
+        let len = patterns[0].len();
+
+        let mut matches = Vec::new();
+        let mut truncated = false;
+
+        // Scan the data a chunk at a time. Each chunk overlaps the next by one byte less than
+        // the length of the pattern, so that matches that cross the boundary are not missed.
+        // Memory-mapped chunks are only copied if there are unsaved edits to overlay onto them.
+        let mut position = 0;
+        'scan: while position < data.len() {
+            let range = position..position + CHUNK_SIZE + len - 1;
+            let mut chunk = data.bytes(range.clone()).map_err(|e| e.to_string())?;
+            if edits.modifies(range) {
+                edits.apply(position, chunk.to_mut());
+            }
+            let n = chunk.len();
+
+            let end = std::cmp::min(CHUNK_SIZE, (n + 1).saturating_sub(len));
+            for i in 0..end {
+                if patterns.iter().any(|p| matches_at(p, &chunk[i..])) {
+                    if matches.len() == MAX_MATCHES {
+                        truncated = true;
+                        break 'scan;
+                    }
+                    matches.push(position + i);
+                }
+            }
+            position += CHUNK_SIZE;
+        }
+
+        Ok(Self {
+            matches,
+            len,
+            truncated,
+        })
+    }
+
+    /// Returns true if the byte at the given index is a part of a match
+    pub fn contains(&self, index: usize) -> bool {
+        // All matches are of the same length, so only the last one to start
+        // at (or before) the index can contain it
+        let i = self.matches.partition_point(|&start| start <= index);
+        i > 0 && index < self.matches[i - 1] + self.len
+    }
+
+    /// Returns the first match that begins after the given index, wrapping around to the start
+    pub fn next(&self, index: usize) -> Option<usize> {
+        let i = self.matches.partition_point(|&start| start <= index);
+        self.matches.get(i).or(self.matches.first()).copied()
+    }
+
+    /// Returns the last match that begins before the given index, wrapping around to the end
+    pub fn previous(&self, index: usize) -> Option<usize> {
+        let i = self.matches.partition_point(|&start| start < index);
+        match i {
+            0 => self.matches.last().copied(),
+            _ => Some(self.matches[i - 1]),
+        }
+    }
+}
+
+/// Returns true if the pattern matches the beginning of the data
+fn matches_at(pattern: &[Option<u8>], data: &[u8]) -> bool {
+    pattern.len() <= data.len()
+        && pattern
+            .iter()
+            .zip(data)
+            .all(|(p, b)| p.is_none_or(|p| p == *b))
+}
+
+/// Parse the query into the patterns to search for (all of which have the same length)
+fn parse(query: &str) -> Result<Vec<Pattern>, String> {
+    if query.is_empty() {
+        return Err(String::from("Nothing to search for"));
+    }
+
+    // UTF-16 strings are searched for in both byte-orders
+    if let Some(text) = query.strip_prefix("u\"") {
+        let text = text.strip_suffix('"').unwrap_or(text);
+        if text.is_empty() {
+            return Err(String::from("Nothing to search for"));
+        }
+        let units: Vec<u16> = text.encode_utf16().collect();
+        let le = units.iter().flat_map(|u| u.to_le_bytes()).map(Some);
+        let be = units.iter().flat_map(|u| u.to_be_bytes()).map(Some);
+        return Ok(vec![le.collect(), be.collect()]);
+    }
+
+    // Quoted strings are always searched for as text
+    if let Some(text) = query.strip_prefix('"') {
+        let text = text.strip_suffix('"').unwrap_or(text);
+        if text.is_empty() {
+            return Err(String::from("Nothing to search for"));
+        }
+        return Ok(vec![text.bytes().map(Some).collect()]);
+    }
+
+    // A pattern of only wildcards (or only whitespace) would match everywhere
+    let pattern = parse_hex(query).unwrap_or_else(|| query.bytes().map(Some).collect());
+    if pattern.iter().all(Option::is_none) {
+        return Err(String::from("Nothing to search for"));
+    }
+    Ok(vec![pattern])
+}
+
+/// Parse a sequence of hex bytes with `??` wildcards, ignoring any whitespace
+fn parse_hex(query: &str) -> Option<Pattern> {
+    let digits: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).collect();
+    if !digits.len().is_multiple_of(2) {
+        return None;
+    }
+    digits
+        .chunks(2)
+        .map(|pair| match pair {
+            ['?', '?'] => Some(None),
+            [hi, lo] => {
+                let byte = (hi.to_digit(16)? << 4) | lo.to_digit(16)?;
+                Some(Some(byte as u8))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_ray::utils::source::Buffered;
+
+    fn search(query: &str, data: &[u8]) -> Search {
+        let mut pager = Pager::new(Box::new(Buffered::from(data.to_vec())), 16);
+        Search::run(query, &mut pager, &Edits::default()).unwrap()
+    }
+
+    #[test]
+    fn should_parse_hex_patterns_with_wildcards() {
+        assert_eq!(
+            parse("de ad ?? EF").unwrap(),
+            vec![vec![Some(0xde), Some(0xad), None, Some(0xef)]]
+        );
+        // Not valid hex, so it is searched for as text
+        assert_eq!(
+            parse("abc").unwrap(),
+            vec![vec![Some(b'a'), Some(b'b'), Some(b'c')]]
+        );
+        assert_eq!(parse("\"ab\"").unwrap(), vec![vec![Some(b'a'), Some(b'b')]]);
+        // Whitespace and wildcards alone would match everywhere
+        assert!(parse("  ").is_err());
+        assert!(parse("?? ??").is_err());
+        assert_eq!(parse("\" \"").unwrap(), vec![vec![Some(b' ')]]);
+    }
+
+    #[test]
+    fn should_parse_utf16_patterns_in_both_byte_orders() {
+        assert_eq!(
+            parse("u\"hi\"").unwrap(),
+            vec![
+                vec![Some(b'h'), Some(0), Some(b'i'), Some(0)],
+                vec![Some(0), Some(b'h'), Some(0), Some(b'i')],
+            ]
+        );
+    }
+
+    #[test]
+    fn should_find_all_matches() {
+        let result = search("\"ab\"", b"ab-ab--ab");
+        assert_eq!(result.matches, vec![0, 3, 7]);
+        assert!(result.contains(4));
+        assert!(!result.contains(5));
+        assert_eq!(result.next(0), Some(3));
+        assert_eq!(result.next(7), Some(0));
+        assert_eq!(result.previous(3), Some(0));
+        assert_eq!(result.previous(0), Some(7));
+    }
+
+    #[test]
+    fn should_find_matches_across_chunk_boundaries() {
+        let mut data = vec![0; CHUNK_SIZE + 8];
+        data[CHUNK_SIZE - 1] = 0xca;
+        data[CHUNK_SIZE] = 0xfe;
+        let result = search("ca fe", &data);
+        assert_eq!(result.matches, vec![CHUNK_SIZE - 1]);
+    }
+
+    #[test]
+    fn should_search_the_unsaved_edits() {
+        let mut pager = Pager::new(Box::new(Buffered::from(vec![0; 4])), 16);
+        let mut edits = Edits::default();
+        edits.set(2, 0x41);
+        let result = Search::run("41", &mut pager, &edits).unwrap();
+        assert_eq!(result.matches, vec![2]);
+    }
+}
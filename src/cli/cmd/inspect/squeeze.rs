@@ -0,0 +1,204 @@
+// Library
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use super::edits::Edits;
+use super::pager::Pager;
+
+/// The number of bytes to compare at a time
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The minimum number of identical rows to collapse. The first and last rows of a run stay in
+/// view on either side of the `*` line, so that the selection can step over it
+pub const MIN_RUN_LEN: usize = 3;
+
+/// The number of runs to remember before forgetting those too short to collapse
+const MAX_CACHED_RUNS: usize = 4096;
+
+/// A line in the view; either a row, or a `*` line that stands in for a run of collapsed rows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Line {
+    Row(usize),
+    Squeezed(Range<usize>),
+}
+
+/// The runs of identical rows found so far, which stay valid until the edits change them
+#[derive(Debug, Default)]
+pub struct Runs {
+    revision: usize, // The revision of the edits that the runs are up to date with
+    runs: BTreeMap<usize, usize>, // The runs, from their first row to the row after their last
+}
+
+impl Runs {
+    /// Returns the run of rows (including the unsaved edits) that are identical to the given row.
+    ///
+    /// Rows that do not repeat make up a run of their own. Only full rows can repeat.
+    pub fn get(
+        &mut self,
+        data: &mut Pager,
+        edits: &Edits,
+        row: usize,
+    ) -> std::io::Result<Range<usize>> {
+        // An edit can change the rows of a run, or make the rows on either side of it join in
+        let size = data.row_size();
+        if self.revision != edits.revision() {
+            match edits.changed_since(self.revision) {
+                Some(ranges) => {
+                    for range in ranges.into_iter().filter(|range| !range.is_empty()) {
+                        let (first, last) = (range.start / size, (range.end - 1) / size);
+                        self.runs
+                            .retain(|&start, &mut end| last + 1 < start || first > end);
+                    }
+                }
+                None => self.runs.clear(),
+            }
+            self.revision = edits.revision();
+        }
+        if let Some((&start, &end)) = self.runs.range(..=row).next_back() {
+            if row < end {
+                return Ok(start..end);
+            }
+        }
+
+        let mut pattern = data.bytes(row * size..(row + 1) * size)?.into_owned();
+        edits.apply(row * size, &mut pattern);
+        let run = if pattern.len() < size {
+            row..row + 1
+        } else {
+            first_repeat(data, edits, &pattern, row)?..end_of_run(data, edits, &pattern, row)?
+        };
+
+        // The short runs are cheap to find again, unlike the ones that may span the whole file
+        if self.runs.len() >= MAX_CACHED_RUNS {
+            self.runs
+                .retain(|&start, &mut end| end - start >= MIN_RUN_LEN);
+        }
+        self.runs.insert(run.start, run.end);
+        Ok(run)
+    }
+}
+
+/// Returns the first row of the run of rows identical to the `pattern` that ends at `row`
+fn first_repeat(
+    data: &mut Pager,
+    edits: &Edits,
+    pattern: &[u8],
+    row: usize,
+) -> std::io::Result<usize> {
+    let size = pattern.len();
+    let rows_per_chunk = std::cmp::max(1, CHUNK_SIZE / size);
+
+    let mut start = row;
+    while start > 0 {
+        let first = start.saturating_sub(rows_per_chunk);
+        let range = first * size..start * size;
+        let mut chunk = data.bytes(range.clone())?;
+        if edits.modifies(range) {
+            edits.apply(first * size, chunk.to_mut());
+        }
+        if let Some(i) = chunk.chunks(size).rposition(|r| r != pattern) {
+            return Ok(first + i + 1);
+        }
+        start = first;
+    }
+    Ok(0)
+}
+
+/// Returns the row after the last of the run of rows identical to the `pattern` that begins at `row`
+fn end_of_run(
+    data: &mut Pager,
+    edits: &Edits,
+    pattern: &[u8],
+    row: usize,
+) -> std::io::Result<usize> {
+    let size = pattern.len();
+    let rows_per_chunk = std::cmp::max(1, CHUNK_SIZE / size);
+
+    let mut end = row + 1;
+    loop {
+        let range = end * size..(end + rows_per_chunk) * size;
+        let mut chunk = data.bytes(range.clone())?;
+        if edits.modifies(range) {
+            edits.apply(end * size, chunk.to_mut());
+        }
+        if chunk.is_empty() {
+            return Ok(end);
+        }
+        // A partial last row never matches
+        if let Some(i) = chunk.chunks(size).position(|r| r != pattern) {
+            return Ok(end + i);
+        }
+        end += chunk.len() / size;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_ray::utils::source::Buffered;
+
+    fn pager(data: &[u8]) -> Pager {
+        Pager::new(Box::new(Buffered::from(data.to_vec())), 2)
+    }
+
+    #[test]
+    fn should_find_runs_of_identical_rows() {
+        let mut data = pager(b"abababababcdcdcdcd");
+        let mut runs = Runs::default();
+        let edits = Edits::default();
+        assert_eq!(runs.get(&mut data, &edits, 2).unwrap(), 0..5);
+        assert_eq!(runs.get(&mut data, &edits, 5).unwrap(), 5..9);
+        assert_eq!(runs.get(&mut data, &edits, 0).unwrap(), 0..5);
+    }
+
+    #[test]
+    fn should_include_the_edits() {
+        let mut data = pager(b"ababababa");
+        let mut runs = Runs::default();
+        let mut edits = Edits::default();
+        assert_eq!(runs.get(&mut data, &edits, 0).unwrap(), 0..4);
+        assert_eq!(runs.get(&mut data, &edits, 4).unwrap(), 4..5);
+
+        edits.set(4, b'x');
+        assert_eq!(runs.get(&mut data, &edits, 0).unwrap(), 0..2);
+        assert_eq!(runs.get(&mut data, &edits, 3).unwrap(), 3..4);
+    }
+
+    #[test]
+    fn should_only_forget_the_runs_around_the_edits() {
+        let mut data = pager(b"ababababcdcdcdcdefefef");
+        let mut runs = Runs::default();
+        let mut edits = Edits::default();
+        assert_eq!(runs.get(&mut data, &edits, 0).unwrap(), 0..4);
+        assert_eq!(runs.get(&mut data, &edits, 4).unwrap(), 4..8);
+        assert_eq!(runs.get(&mut data, &edits, 8).unwrap(), 8..11);
+
+        edits.set(20, b'c');
+        edits.set(21, b'd');
+        runs.get(&mut data, &edits, 0).unwrap();
+        assert_eq!(runs.runs.keys().copied().collect::<Vec<_>>(), vec![0, 4]);
+        assert_eq!(runs.get(&mut data, &edits, 9).unwrap(), 8..10);
+        assert_eq!(runs.get(&mut data, &edits, 10).unwrap(), 10..11);
+    }
+
+    #[test]
+    fn should_remember_the_rows_that_do_not_repeat() {
+        let mut data = pager(b"abcdcdefg");
+        let mut runs = Runs::default();
+        let mut edits = Edits::default();
+        assert_eq!(runs.get(&mut data, &edits, 0).unwrap(), 0..1);
+        assert_eq!(runs.get(&mut data, &edits, 2).unwrap(), 1..3);
+        assert_eq!(runs.get(&mut data, &edits, 3).unwrap(), 3..4);
+        assert_eq!(runs.get(&mut data, &edits, 4).unwrap(), 4..5);
+        assert_eq!(
+            runs.runs.keys().copied().collect::<Vec<_>>(),
+            vec![0, 1, 3, 4]
+        );
+
+        edits.set(0, b'c');
+        edits.set(1, b'd');
+        runs.get(&mut data, &edits, 4).unwrap();
+        assert_eq!(runs.runs.keys().copied().collect::<Vec<_>>(), vec![3, 4]);
+        assert_eq!(runs.get(&mut data, &edits, 0).unwrap(), 0..3);
+    }
+}
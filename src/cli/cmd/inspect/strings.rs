@@ -0,0 +1,230 @@
+// Library
+use super::edits::Edits;
+use super::pager::Pager;
+use hex_ray::strings::Found;
+use hex_ray::{Scanner, StringEncoding};
+
+/// The maximum number of strings to collect
+const MAX_STRINGS: usize = 100_000;
+
+/// The number of bytes to scan at a time
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The number of bytes to scan between frames, so that the view stays responsive
+const SCAN_SIZE: usize = 16 * CHUNK_SIZE;
+
+/// The minimum number of characters in a string
+pub const MIN_LEN: usize = 4;
+
+/// The encodings of the strings listed in the panel
+const ENCODINGS: [StringEncoding; 3] = [
+    StringEncoding::Ascii,
+    StringEncoding::Utf16le,
+    StringEncoding::Utf16be,
+];
+
+/// The strings found in the data, as listed in the side panel.
+///
+/// The data is scanned a part at a time between frames, and scanned over again whenever the
+/// unsaved edits change.
+#[derive(Debug, Default)]
+pub struct StringList {
+    pub strings: Vec<Found>, // The (sorted) strings, with the offsets as indices into the data
+    reach: Vec<usize>,       // The furthest end of the strings up to (and including) each string
+    pub truncated: bool,     // Whether there were more strings than were collected
+    pub selected: usize,     // The index of the highlighted string
+    pub scroll_offset: usize, // The index of the first string in view
+    scanner: Option<Scanner>, // The scanner, while there is data left to scan
+    position: usize,         // The index of the next byte to scan
+    revision: usize,         // The revision of the edits that the data is scanned with
+    follow: Option<usize>, // The index to highlight the first string at (or after), until one is selected
+}
+
+impl StringList {
+    /// Start scanning the data (along with the unsaved edits), highlighting the first string at
+    /// (or after) the given index as the strings are found
+    pub fn new(edits: &Edits, index: usize) -> Self {
+        Self {
+            scanner: Some(Scanner::new(&ENCODINGS, MIN_LEN, 0)),
+            revision: edits.revision(),
+            follow: Some(index),
+            ..Self::default()
+        }
+    }
+
+    /// Returns true while there is data left to scan
+    pub fn is_scanning(&self) -> bool {
+        self.scanner.is_some()
+    }
+
+    /// Scan the next part of the data, keeping the highlight in a view of `height` strings.
+    /// Returns true if the scan was completed by this call
+    pub fn scan(
+        &mut self,
+        data: &mut Pager,
+        edits: &Edits,
+        height: usize,
+    ) -> std::io::Result<bool> {
+        if self.revision != edits.revision() {
+            let index = match (self.follow, self.strings.get(self.selected)) {
+                (None, Some(found)) => found.offset,
+                (follow, _) => follow.unwrap_or(0),
+            };
+            *self = Self::new(edits, index);
+        }
+        let Some(scanner) = self.scanner.as_mut() else {
+            return Ok(false);
+        };
+
+        // Memory-mapped chunks are only copied if there are unsaved edits to overlay onto them
+        let mut strings = Vec::new();
+        let end = self.position + SCAN_SIZE;
+        while self.position < std::cmp::min(end, data.len()) {
+            let range = self.position..self.position + CHUNK_SIZE;
+            let mut chunk = data.bytes(range.clone())?;
+            if edits.modifies(range) {
+                edits.apply(self.position, chunk.to_mut());
+            }
+            strings.extend(scanner.feed(&chunk));
+            self.position += CHUNK_SIZE;
+        }
+        if self.position >= data.len() {
+            strings.extend(self.scanner.take().into_iter().flat_map(Scanner::finish));
+        }
+
+        // The strings come in order of their offsets, so they are only sorted within the batch
+        strings.sort_by_key(|found| (found.offset, std::cmp::Reverse(found.len)));
+        for found in strings {
+            if self.strings.len() == MAX_STRINGS {
+                self.scanner = None;
+                self.truncated = true;
+                break;
+            }
+            self.push(found);
+        }
+
+        if let Some(index) = self.follow {
+            self.highlight(self.position(index), height);
+        }
+        Ok(!self.is_scanning())
+    }
+
+    /// Add the next string to the list.
+    ///
+    /// ASCII text in UTF-16 also reads as a run of the other byte-order, one byte over. The
+    /// strings that lie within another string are left out, and of two overlapping UTF-16
+    /// runs, only the longer one (or the little-endian one) is kept
+    fn push(&mut self, found: Found) {
+        let rank = |found: &Found| (found.len, found.encoding == StringEncoding::Utf16le);
+        let end = found.offset + found.len;
+        if end <= self.reach.last().copied().unwrap_or(0) {
+            return;
+        }
+        if let Some(last) = self.strings.last() {
+            if is_utf16(last)
+                && is_utf16(&found)
+                && last.encoding != found.encoding
+                && found.offset < last.offset + last.len
+            {
+                if rank(&found) <= rank(last) {
+                    return;
+                }
+                self.strings.pop();
+                self.reach.pop();
+            }
+        }
+        self.reach.push(end);
+        self.strings.push(found);
+    }
+
+    /// Returns true if the byte at the given index is a part of a string
+    pub fn contains(&self, index: usize) -> bool {
+        // The ends only grow from one string to the next, so the strings that begin at (or
+        // before) the index contain it if the furthest of them reaches beyond it
+        let i = self.strings.partition_point(|found| found.offset <= index);
+        i > 0 && index < self.reach[i - 1]
+    }
+
+    /// Highlight the string at the given position in the list (clamped to the list), keeping
+    /// it in a view of `height` strings. Returns the index of its first byte
+    pub fn select(&mut self, selected: usize, height: usize) -> Option<usize> {
+        self.follow = None;
+        self.highlight(selected, height)
+    }
+
+    /// Highlight the string at the given position, without selecting it
+    fn highlight(&mut self, selected: usize, height: usize) -> Option<usize> {
+        self.selected = std::cmp::min(selected, self.strings.len().checked_sub(1)?);
+        if self.selected < self.scroll_offset {
+            self.scroll_offset = self.selected;
+        } else if self.selected >= self.scroll_offset + height {
+            self.scroll_offset = self.selected + 1 - height;
+        }
+        Some(self.strings[self.selected].offset)
+    }
+
+    /// Returns the position in the list of the first string that ends after the given index
+    pub fn position(&self, index: usize) -> usize {
+        let i = self.reach.partition_point(|&end| end <= index);
+        std::cmp::min(i, self.strings.len().saturating_sub(1))
+    }
+}
+
+/// Returns true if the string was decoded as UTF-16 (in either byte-order)
+fn is_utf16(found: &Found) -> bool {
+    matches!(
+        found.encoding,
+        StringEncoding::Utf16le | StringEncoding::Utf16be
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_ray::utils::source::Buffered;
+
+    fn scan(data: &[u8]) -> StringList {
+        let mut pager = Pager::new(Box::new(Buffered::from(data.to_vec())), 16);
+        let edits = Edits::default();
+        let mut list = StringList::new(&edits, 0);
+        while !list.scan(&mut pager, &edits, 10).unwrap() {}
+        list
+    }
+
+    #[test]
+    fn should_list_ascii_and_utf16_strings() {
+        let list = scan(b"\x01hello\x00\x00W\x00o\x00r\x00l\x00d\x00\x00\x02\x00x\x00y\x00z\x00w");
+        let strings: Vec<_> = list
+            .strings
+            .iter()
+            .map(|f| (f.offset, f.encoding, f.text.as_str()))
+            .collect();
+        assert_eq!(
+            strings,
+            vec![
+                (1, StringEncoding::Ascii, "hello"),
+                (8, StringEncoding::Utf16le, "World"),
+                (20, StringEncoding::Utf16be, "xyzw"),
+            ]
+        );
+        assert!(list.contains(5));
+        assert!(!list.contains(6));
+        assert!(list.contains(17));
+        assert!(!list.contains(18));
+        assert_eq!(list.position(6), 1);
+    }
+
+    #[test]
+    fn should_scan_again_when_the_edits_change() {
+        let mut pager = Pager::new(Box::new(Buffered::from(b"hello\x00world".to_vec())), 16);
+        let mut edits = Edits::default();
+        let mut list = StringList::new(&edits, 0);
+        while !list.scan(&mut pager, &edits, 10).unwrap() {}
+        assert_eq!(list.strings.len(), 2);
+
+        edits.set(5, b'_');
+        assert!(list.scan(&mut pager, &edits, 10).unwrap());
+        let texts: Vec<_> = list.strings.iter().map(|f| f.text.as_str()).collect();
+        assert_eq!(texts, vec!["hello_world"]);
+    }
+}
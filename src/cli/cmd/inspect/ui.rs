@@ -4,11 +4,38 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Padding, Paragraph};
 use ratatui::Frame;
 
-use crate::utils::format::Format;
-use crate::utils::helpers;
+use hex_ray::utils::ansi;
+use hex_ray::utils::encoding::Encoding;
+use hex_ray::utils::format::{Endianness, Format};
+use hex_ray::utils::theme::ByteClass;
+use hex_ray::StringEncoding;
 
+use super::app::Focus;
+use super::inspector::{self, Interpretation};
+use super::keys::Action;
+use super::squeeze;
 use super::App;
 
+/// The width of the strings panel (including the borders)
+const STRINGS_WIDTH: u16 = 40;
+
+/// The value of the selected byte in one of the files
+struct Selection {
+    value: String,                  // The formatted value beginning at the selected byte
+    ascii: Span<'static>,           // The ASCII representation of the selected byte
+    byte: u8,                       // The selected byte
+    inspector: Vec<Interpretation>, // The selected bytes, interpreted as each type
+}
+
+/// The contents of the columns that make up the hex-dump of one of the files
+#[derive(Default)]
+struct Pane {
+    offsets: Vec<Line<'static>>,  // The lines of the offset column
+    hex: Vec<Line<'static>>,      // The lines of the hex values column
+    ascii: Vec<Line<'static>>,    // The lines of the ASCII values column
+    selection: Option<Selection>, // The selected byte, if it is in view
+}
+
 impl App {
     /// Draw the UI to the screen
     pub fn draw(&self, f: &mut Frame) {
@@ -30,184 +57,521 @@ impl App {
         f.render_widget(self.header(), base_layout[0]);
 
         // Calculate column widths based on format and configuration
-        let offset_len = 8 + 4; // 8 digits + (2 space + 2 borders)
-        let hex_len = ((self.cfg.format.size() + 1) * self.cfg.size) // Format size (e.g. 2 for Hex) + 1 whitespace
-            + (self.cfg.size / self.cfg.group_size) // Extra whitespace for group separators
+        let offset_len = self.offset_width() as u16 + 4; // Digits + (2 space + 2 borders)
+        let values = self.dumper.values_per_row();
+        let hex_len = ((self.cfg.format.size() + 1) * values) // Format size (e.g. 2 for Hex) + 1 whitespace
+            + (0..values).filter(|&i| self.dumper.is_group_start(i)).count() // Extra whitespace for group separators
             + 4; // + 2 outer space + 2 borders
-        let ascii_len = (self.cfg.size + 1) + (self.cfg.size / self.cfg.group_size) + 2; // (1 ASCII char + 1 whitespace) + (group spacing) + borders
+        let ascii_len = (self.cfg.size * self.cfg.substitution.width() + 1)
+            + (self.cfg.size / self.cfg.group_size)
+            + 2; // (1 ASCII char (or 2 in caret notation) + 1 whitespace) + (group spacing) + borders
 
-        // Create a layout with three vertical sections
+        // The file to compare against is shown in a second set of columns, next to the first
+        let mut panes = vec![(self.pane(false), self.cfg.filepath.as_ref())];
+        if self.compare.is_some() {
+            panes.push((self.pane(true), self.compare_path.as_ref()));
+        }
+
+        // Create a layout with three vertical sections for each file
+        let mut constraints = Vec::new();
+        for _ in &panes {
+            constraints.extend([
+                Constraint::Length(offset_len),       // Offset
+                Constraint::Length(hex_len as u16),   // Hex Values
+                Constraint::Length(ascii_len as u16), // ASCII Values
+            ]);
+        }
+        if self.strings.is_some() {
+            constraints.push(Constraint::Length(STRINGS_WIDTH)); // Strings Panel
+        }
+        constraints.push(Constraint::Fill(1)); // Selection Block
         let columns = Layout::default()
             .direction(Direction::Horizontal)
-            .constraints(
-                [
-                    Constraint::Length(offset_len),       // Offset
-                    Constraint::Length(hex_len as u16),   // Hex Values
-                    Constraint::Length(ascii_len as u16), // ASCII Values
-                    Constraint::Fill(1),                  // Selection Block
-                ]
-                .as_ref(),
-            )
+            .constraints(constraints)
             .split(base_layout[1]);
 
-        // Create a block with borders and title for each column
-        let offset_block = Block::default().borders(Borders::ALL);
-        let hex_block = Block::default().borders(Borders::ALL);
-        let ascii_block = Block::default().borders(Borders::ALL);
+        let mut selections = Vec::new();
+        for (i, (pane, path)) in panes.into_iter().enumerate() {
+            // Create a block with borders for each column, titled with the file name when comparing
+            let offset_block = Block::default().borders(Borders::ALL);
+            let mut hex_block = Block::default()
+                .borders(Borders::ALL)
+                .padding(Padding::horizontal(1));
+            if self.compare.is_some() {
+                let name = path.map_or(String::from("STDIN"), |p| p.to_string_lossy().to_string());
+                hex_block = hex_block.title(format!(" {} ", name));
+            }
+            let mut ascii_block = Block::default()
+                .borders(Borders::ALL)
+                .padding(Padding::left(1));
+            if self.encoding != Encoding::Ascii {
+                ascii_block = ascii_block.title(format!(" {} ", self.encoding.name()));
+            }
+
+            // Create the block paragraphs and add them to the main section
+            let offset_paragraph = Paragraph::new(pane.offsets)
+                .block(offset_block)
+                .alignment(Alignment::Center);
+            let hex_paragraph = Paragraph::new(pane.hex)
+                .block(hex_block)
+                .alignment(Alignment::Left)
+                .style(Color::White);
+            let ascii_paragraph = Paragraph::new(pane.ascii)
+                .block(ascii_block)
+                .alignment(Alignment::Left)
+                .style(Color::White);
+
+            f.render_widget(offset_paragraph, columns[i * 3]);
+            f.render_widget(hex_paragraph, columns[i * 3 + 1]);
+            f.render_widget(ascii_paragraph, columns[i * 3 + 2]);
+            selections.push(pane.selection);
+        }
+
+        if self.strings.is_some() {
+            f.render_widget(self.strings_panel(), columns[columns.len() - 2]);
+        }
+
         let selection_block = Block::default().padding(Padding::symmetric(4, 1));
+        let selection_paragraph =
+            Paragraph::new(self.format_selection_block(&selections)).block(selection_block);
+        f.render_widget(selection_paragraph, columns[columns.len() - 1]);
 
-        let mut offset_data = Vec::new();
-        let mut hex_data = Vec::new();
-        let mut ascii_data = Vec::new();
-        let mut selection_data = Vec::new();
+        // Render the Help component
+        f.render_widget(self.help(), base_layout[2]);
+    }
 
+    /// Render the columns of the rows in view, either of the data or of the file to compare against
+    fn pane(&self, compare: bool) -> Pane {
+        let mut pane = Pane::default();
+        let pager = match (compare, &self.compare) {
+            (false, _) => &self.data,
+            (true, Some(pager)) => pager,
+            (true, None) => return pane,
+        };
+
+        let palette = self.cfg.palette;
         // Describe the style of the selected element
         let selected_style = Style::default()
-            .bg(Color::Rgb(255, 146, 92))
+            .bg(tui_color(palette.selected))
             .fg(Color::Black)
             .bold();
+        // ... and of the selected element in the column that does not have the focus in edit mode
+        let unfocused_style = Style::default()
+            .fg(tui_color(palette.selected))
+            .bold()
+            .underlined();
+        let (hex_selected_style, ascii_selected_style) = match (self.editing, self.focus) {
+            (true, _) if compare => (unfocused_style, unfocused_style), // Only the data is editable
+            (true, Focus::Hex) => (selected_style, unfocused_style),
+            (true, Focus::Ascii) => (unfocused_style, selected_style),
+            (false, _) => (selected_style, selected_style),
+        };
+        // Describe the style of the bytes that have been modified
+        let modified_style = Style::default().fg(tui_color(palette.modified)).bold();
+        // Describe the style of the bytes that match the search
+        let match_style = Style::default()
+            .bg(tui_color(palette.matched))
+            .fg(Color::Black);
+        // Describe the style of the bytes in the selected range
+        let range_style = Style::default()
+            .bg(tui_color(palette.range))
+            .fg(Color::White);
+        let (first, last) = self.range();
+        // Describe the colors of each class of bytes
+        let class_style = |class: ByteClass| Style::default().fg(tui_color(palette.color(class)));
+        // Describe the style of the bytes that differ between the files
+        let different_style = Style::default().fg(tui_color(palette.different)).bold();
+        // Describe the style of the bytes of the strings listed in the strings panel
+        let string_style = Style::default().underlined();
+        // Determine the style of the (unselected) byte at the given index
+        let style_of = |index: usize| {
+            let mut style = Style::default();
+            if self.anchor.is_some() && (first..=last).contains(&index) {
+                style = style.patch(range_style);
+            }
+            if self.differs(index) {
+                style = style.patch(different_style);
+            }
+            if !compare && self.search.as_ref().is_some_and(|s| s.contains(index)) {
+                style = style.patch(match_style);
+            }
+            if !compare && self.strings.as_ref().is_some_and(|s| s.contains(index)) {
+                style = style.patch(string_style);
+            }
+            if !compare && self.edits.get(index).is_some() {
+                style = style.patch(modified_style);
+            }
+            style
+        };
 
-        // Determine the starting and ending rows for the data slice
-        let start = self.scroll_offset;
-        let end = std::cmp::min(
-            self.scroll_offset + self.rows(self.rows_per_page),
-            self.data.len(),
-        );
+        // Iterate over the lines in view ...
+        for line in &self.lines {
+            // The collapsed rows of a run of identical rows are marked by a single `*` line
+            let row_index = match line {
+                squeeze::Line::Row(row_index) => *row_index,
+                squeeze::Line::Squeezed(rows) => {
+                    pane.offsets.push(Line::from("*".dark_gray()));
+                    pane.hex.push(Line::from(
+                        format!("{} identical rows", rows.len()).dark_gray(),
+                    ));
+                    pane.ascii.push(Line::default());
+                    continue;
+                }
+            };
 
-        // Iterate over the data slice ...
-        for (i, row) in self.data[start..end].iter().enumerate() {
-            let row_index = start + i; // The absolute row index
+            // The rows in view are paged in before every draw. Rows beyond the end of the
+            // shorter file are left empty, to keep the rows of both files aligned
+            let Some(row) = pager.get(row_index) else {
+                pane.offsets.push(Line::default());
+                pane.hex.push(Line::default());
+                pane.ascii.push(Line::default());
+                continue;
+            };
             let is_selected_row = self.row(self.selected) == row_index;
 
             // Offset column
-            let offset_str = row.format_offset();
+            let offset_str = row.format_offset(self.cfg.radix, self.offset_width());
             let offset_spans = if is_selected_row {
-                Span::from(offset_str.bold().white())
+                offset_str.bold().white()
             } else {
                 Span::from(offset_str)
             };
-            offset_data.push(Line::from(offset_spans));
+            pane.offsets.push(Line::from(offset_spans));
+
+            // Overlay the unsaved edits onto the row
+            let mut data = row.data.clone();
+            if !compare {
+                self.edits.apply(self.rows(row_index), &mut data);
+            }
 
             // Hex Values column
             let mut hex_spans = Vec::new();
-            let mut ascii_spans = Vec::new();
-            for (j, byte) in row.data.iter().enumerate() {
+            let bytes_per_value = self.cfg.format.bytes();
+            for (j, bytes) in data.chunks(bytes_per_value).enumerate() {
                 // Group values by applying spacing
-                if j > 0 && j % self.cfg.group_size == 0 {
+                if self.dumper.is_group_start(j) {
                     hex_spans.push(Span::from(" "));
-                    ascii_spans.push(Span::from(" "));
                 }
 
-                // Format the byte and ascii values
-                let byte_str = self.cfg.format.format(*byte);
-                let ascii_str = if helpers::is_printable_ascii_character(byte) {
-                    Span::from((*byte as char).to_string())
+                // If the selected byte is a part of this value, style it differently
+                let value_str = self.cfg.format.format_bytes(bytes);
+                let span = (self.rows(row_index) + j * bytes_per_value)..;
+                let span = span.start..span.start + bytes.len();
+                if span.contains(&self.selected) {
+                    hex_spans.push(Span::styled(value_str, hex_selected_style));
                 } else {
-                    Span::from("·".dark_gray())
-                };
+                    let base = class_style(ByteClass::of_bytes(bytes));
+                    let style = span.map(style_of).fold(base, Style::patch);
+                    hex_spans.push(Span::styled(value_str, style));
+                }
+                hex_spans.push(Span::from(" "));
+            }
+
+            // ASCII Values column (or the characters in the selected encoding)
+            let glyphs = self.glyphs(row_index, &data, compare);
+            let mut ascii_spans = Vec::new();
+            for (j, (byte, glyph)) in data.iter().zip(&glyphs).enumerate() {
+                // Group characters by applying spacing
+                if j > 0 && j % self.cfg.group_size == 0 {
+                    ascii_spans.push(Span::from(" "));
+                }
+
+                let ascii_str = Span::from(self.cfg.substitution.render(*glyph))
+                    .style(class_style(ByteClass::of(*byte)));
 
                 // If this is the selected element, style it differently
-                if row_index * self.cfg.size + j == self.selected {
-                    selection_data = self.format_selection_block(&byte_str, &ascii_str, byte);
-                    hex_spans.push(Span::styled(byte_str, selected_style));
-                    ascii_spans.push(ascii_str.style(selected_style));
+                let index = self.rows(row_index) + j;
+                if index == self.selected {
+                    // The data inspector interprets the bytes beyond the end of the row as well
+                    let bytes: Vec<u8> = (index..index + inspector::MAX_LEN)
+                        .map_while(|i| match compare {
+                            true => self.compare_byte(i),
+                            false => self.byte(i),
+                        })
+                        .collect();
+                    pane.selection = Some(Selection {
+                        value: self.cfg.format.format_bytes(&data[j..]),
+                        ascii: ascii_str.clone(),
+                        byte: *byte,
+                        inspector: inspector::interpret(&bytes, self.endianness),
+                    });
+                    ascii_spans.push(ascii_str.style(ascii_selected_style));
+                } else if style_of(index) != Style::default() {
+                    ascii_spans.push(ascii_str.patch_style(style_of(index)));
                 } else {
-                    // Otherwise, just add them as is
-                    hex_spans.push(Span::from(byte_str));
                     ascii_spans.push(ascii_str);
-                };
-                hex_spans.push(Span::from(" "));
+                }
             }
 
             // Add the spans to the line
-            hex_data.push(Line::from(hex_spans));
-            ascii_data.push(Line::from(ascii_spans));
+            pane.hex.push(Line::from(hex_spans));
+            pane.ascii.push(Line::from(ascii_spans));
         }
 
-        // Create the block paragraphs and add them to the main section
-        let offset_paragraph = Paragraph::new(offset_data)
-            .block(offset_block)
-            .alignment(Alignment::Center);
-        let hex_paragraph = Paragraph::new(hex_data)
-            .block(hex_block)
-            .alignment(Alignment::Center)
-            .style(Color::White);
-        let ascii_paragraph = Paragraph::new(ascii_data)
-            .block(ascii_block)
-            .alignment(Alignment::Center)
-            .style(Color::White);
-        let selection_paragraph = Paragraph::new(selection_data).block(selection_block);
+        pane
+    }
 
-        f.render_widget(offset_paragraph, columns[0]);
-        f.render_widget(hex_paragraph, columns[1]);
-        f.render_widget(ascii_paragraph, columns[2]);
-        f.render_widget(selection_paragraph, columns[3]);
+    /// Render the strings panel, listing the strings in view with their offsets
+    fn strings_panel(&self) -> Paragraph<'static> {
+        let mut block = Block::default()
+            .borders(Borders::ALL)
+            .padding(Padding::horizontal(1));
+        let Some(list) = &self.strings else {
+            return Paragraph::new(Vec::<Line>::new()).block(block);
+        };
+        let count = match (list.is_scanning(), list.truncated) {
+            (true, _) => format!("{}, scanning", list.strings.len()),
+            (false, true) => format!("{}+", list.strings.len()),
+            (false, false) => list.strings.len().to_string(),
+        };
+        block = block.title(format!(" Strings ({}) ", count));
 
-        // Render the Help component
-        f.render_widget(self.help(), base_layout[2]);
+        let selected_style = Style::default()
+            .bg(tui_color(self.cfg.palette.selected))
+            .fg(Color::Black);
+        let offset_width = self.offset_width();
+        // The room left for the text, after the offset, the encoding and the borders
+        let text_width = (STRINGS_WIDTH as usize).saturating_sub(offset_width + 8);
+
+        let mut lines = Vec::new();
+        let end = std::cmp::min(list.scroll_offset + self.rows_per_page, list.strings.len());
+        for (i, found) in list.strings[list.scroll_offset..end].iter().enumerate() {
+            let offset = self
+                .cfg
+                .radix
+                .format((self.data.start() + found.offset) as u64);
+            let encoding = match found.encoding {
+                StringEncoding::Utf16le => "le",
+                StringEncoding::Utf16be => "be",
+                _ => "  ",
+            };
+            let mut text: String = found.text.chars().take(text_width).collect();
+            text = text.replace('\t', " ");
+
+            let mut line = Line::from(vec![
+                format!("{:0>1$} ", offset, offset_width).dark_gray(),
+                format!("{} ", encoding).dark_gray(),
+                Span::from(text).white(),
+            ]);
+            if list.scroll_offset + i == list.selected {
+                line = line.style(selected_style);
+            }
+            lines.push(line);
+        }
+        if list.strings.is_empty() && !list.is_scanning() {
+            lines.push(Line::from("No strings found".dark_gray()));
+        }
+        Paragraph::new(lines).block(block)
     }
 
     /// Render the header
     fn header(&self) -> Paragraph<'static> {
-        Paragraph::new("·• Hex·Ray •·")
-            .alignment(Alignment::Center)
-            .bold()
-            .white()
+        let mut title = vec![Span::from("·• Hex·Ray •·").bold().white()];
+        // Indicate that there are unsaved changes
+        if !self.edits.is_empty() {
+            title.push(Span::from(format!("  [modified: {}]", self.edits.len())).light_red());
+        }
+        Paragraph::new(Line::from(title)).alignment(Alignment::Center)
     }
 
-    /// Render the selection block
-    fn format_selection_block(
-        &self,
-        byte_str: &String,
-        ascii_str: &Span<'static>,
-        byte: &u8,
-    ) -> Vec<Line> {
-        vec![
-            Line::from(vec![
-                Span::from("Index: "),
-                Span::from(self.selected.to_string().white()),
-            ]),
-            Line::from(vec![
-                Span::from("\nSelected:    "),
-                Span::from(byte_str.clone().white()),
-            ]),
+    /// Render the selection block, with the value of the selected byte in each file side by side
+    fn format_selection_block(&self, selections: &[Option<Selection>]) -> Vec<Line<'_>> {
+        if selections.iter().all(Option::is_none) {
+            return Vec::new();
+        }
+
+        // Combine the values of the selected byte in each file into a single line.
+        // Files that end before the selected byte show a placeholder instead
+        let values = |label: String, value: &dyn Fn(&Selection) -> Span<'static>| {
+            let mut spans = vec![Span::from(label)];
+            for (i, selection) in selections.iter().enumerate() {
+                if i > 0 {
+                    spans.push(Span::from("  "));
+                }
+                spans.push(match selection {
+                    Some(selection) => value(selection).white(),
+                    None => "--".dark_gray(),
+                });
+            }
+            Line::from(spans)
+        };
+
+        let mut lines = vec![Line::from(vec![
+            Span::from("Index: "),
+            self.selected.to_string().white(),
+        ])];
+
+        // The bounds of the selected range, with the offsets including the starting offset
+        if self.anchor.is_some() {
+            let (first, last) = self.range();
+            let start = self.data.start();
+            lines.push(Line::from(vec![
+                Span::from("Range: "),
+                format!("{:#x} - {:#x}", start + first, start + last).white(),
+                format!(" ({} bytes)", last - first + 1).dark_gray(),
+            ]));
+        }
+
+        // The characters are labelled with their encoding
+        let text_label = match self.encoding {
+            Encoding::Ascii => String::from("ASCII:"),
+            encoding => format!("{}:", encoding.name().to_uppercase()),
+        };
+        lines.extend([
+            values(String::from("\nSelected:    "), &|s| {
+                Span::from(s.value.clone())
+            }),
             Line::from("\n"),
-            Line::from(vec![
-                Span::from("\nASCII:       "),
-                Span::from(ascii_str.clone()).white(),
-            ]),
-            Line::from(vec![
-                Span::from("\nBinary:      "),
-                Span::from(Format::Binary.format(*byte).white()),
-            ]),
-            Line::from(vec![
-                Span::from("\nHexadecimal: "),
-                Span::from(Format::Hex.format(*byte).white()),
-            ]),
-            Line::from(vec![
-                Span::from("\nDecimal:     "),
-                Span::from(Format::Decimal.format(*byte).white()),
-            ]),
-            Line::from(vec![
-                Span::from("\nOctal:       "),
-                Span::from(Format::Octal.format(*byte).white()),
-            ]),
-        ]
+            values(format!("\n{:<13}", text_label), &|s| s.ascii.clone()),
+            values(String::from("\nBinary:      "), &|s| {
+                Span::from(Format::Binary.format(s.byte))
+            }),
+            values(String::from("\nHexadecimal: "), &|s| {
+                Span::from(Format::Hex.format(s.byte))
+            }),
+            values(String::from("\nDecimal:     "), &|s| {
+                Span::from(Format::Decimal.format(s.byte))
+            }),
+            values(String::from("\nOctal:       "), &|s| {
+                Span::from(Format::Octal.format(s.byte))
+            }),
+        ]);
+
+        // The data inspector, with the bytes beginning at the selected byte interpreted as each type
+        let endianness = match self.endianness {
+            Endianness::Little => "little-endian",
+            Endianness::Big => "big-endian",
+        };
+        lines.push(Line::from("\n"));
+        lines.push(Line::from(vec![
+            Span::from("Data Inspector "),
+            format!("({})", endianness).dark_gray(),
+        ]));
+        let labels = selections.iter().flatten().next().map(|s| &s.inspector);
+        for (k, (label, _)) in labels.into_iter().flatten().enumerate() {
+            lines.push(values(
+                format!("{:<15}", label),
+                &|s| match &s.inspector[k].1 {
+                    Some(value) => Span::from(value.clone()),
+                    None => "--".dark_gray(),
+                },
+            ));
+        }
+
+        lines
     }
 
     // Render the help line
     fn help(&self) -> Paragraph<'static> {
+        // The prompt and status messages take the place of the help text
+        if let Some(prompt) = &self.prompt {
+            let input = match prompt.is_confirmation() {
+                true => String::new(),
+                false => prompt.input.clone(),
+            };
+            let line = Line::from(vec![
+                Span::styled(prompt.label(), Style::default().fg(Color::Green)),
+                Span::from(input).white(),
+                Span::from("█").white(),
+            ]);
+            return Paragraph::new(line).alignment(Alignment::Center);
+        }
+        if let Some(status) = &self.status {
+            return Paragraph::new(status.clone())
+                .alignment(Alignment::Center)
+                .white();
+        }
+
+        let key = |s: String| Span::styled(s, Style::default().fg(Color::Green));
+        let desc = |s: &'static str| Span::styled(s, Style::default().fg(Color::DarkGray));
+        let separator = || desc("  •  ");
+        // The keys bound to the actions, as configured
+        let keys = |actions: &[Action]| {
+            let labels: Vec<&str> = actions.iter().map(|a| self.keys.label(*a)).collect();
+            format!("{}  ", labels.join(" "))
+        };
+
         // Help text styled and combined into a single line
-        let help_text = vec![
-            Span::styled("q / esc  ", Style::default().fg(Color::Green)),
-            Span::styled("Quit", Style::default().fg(Color::DarkGray)),
-            Span::styled("  •  ", Style::default().fg(Color::DarkGray)),
-            Span::styled(
-                "pgup home ← ↑ ↓ → end pgdn ",
-                Style::default().fg(Color::Green),
-            ),
-            Span::styled("Move selection", Style::default().fg(Color::DarkGray)),
-        ];
+        let help_text = if self.editing {
+            vec![
+                key(String::from("esc  ")),
+                desc("Stop editing"),
+                separator(),
+                key(String::from("tab  ")),
+                desc("Switch column"),
+                separator(),
+                key(String::from("ctrl+s  ")),
+                desc("Save"),
+                separator(),
+                key(String::from("ctrl+z ctrl+y  ")),
+                desc("Undo/Redo"),
+                separator(),
+                key(keys(&[
+                    Action::Left,
+                    Action::Up,
+                    Action::Down,
+                    Action::Right,
+                ])),
+                desc("Move selection"),
+            ]
+        } else {
+            vec![
+                key(format!("{} / esc  ", self.keys.label(Action::Quit))),
+                desc("Quit"),
+                separator(),
+                key(keys(&[Action::Edit])),
+                desc("Edit"),
+                separator(),
+                key(keys(&[Action::Save])),
+                desc("Save"),
+                separator(),
+                key(keys(&[Action::Undo, Action::Redo])),
+                desc("Undo/Redo"),
+                separator(),
+                key(keys(&[
+                    Action::Search,
+                    Action::NextMatch,
+                    Action::PreviousMatch,
+                ])),
+                desc("Search"),
+                separator(),
+                key(keys(&[Action::NextDifference, Action::PreviousDifference])),
+                desc("Next/Previous difference"),
+                separator(),
+                key(keys(&[Action::Endianness])),
+                desc("Endianness"),
+                separator(),
+                key(keys(&[Action::Encoding])),
+                desc("Encoding"),
+                separator(),
+                key(keys(&[Action::SelectRange])),
+                desc("Select range"),
+                separator(),
+                key(keys(&[Action::Squeeze])),
+                desc("Squeeze"),
+                separator(),
+                key(keys(&[Action::Strings])),
+                desc("Strings"),
+                separator(),
+                key(keys(&[Action::Copy])),
+                desc("Copy"),
+                separator(),
+                key(keys(&[Action::GoTo])),
+                desc("Go to"),
+                separator(),
+                key(keys(&[
+                    Action::PageUp,
+                    Action::Home,
+                    Action::Left,
+                    Action::Up,
+                    Action::Down,
+                    Action::Right,
+                    Action::End,
+                    Action::PageDown,
+                ])),
+                desc("Move selection"),
+            ]
+        };
         let help_line = Line::from(help_text);
 
         Paragraph::new(help_line)
@@ -215,3 +579,28 @@ impl App {
             .wrap(ratatui::widgets::Wrap { trim: false })
     }
 }
+
+/// Convert a color of the palette into the corresponding terminal color
+fn tui_color(color: ansi::Color) -> Color {
+    match color {
+        ansi::Color::Black => Color::Black,
+        ansi::Color::Red => Color::Red,
+        ansi::Color::Green => Color::Green,
+        ansi::Color::Yellow => Color::Yellow,
+        ansi::Color::Blue => Color::Blue,
+        ansi::Color::Magenta => Color::Magenta,
+        ansi::Color::Cyan => Color::Cyan,
+        ansi::Color::White => Color::Gray,
+        ansi::Color::BrightBlack => Color::DarkGray,
+        ansi::Color::BrightRed => Color::LightRed,
+        ansi::Color::BrightGreen => Color::LightGreen,
+        ansi::Color::BrightYellow => Color::LightYellow,
+        ansi::Color::BrightBlue => Color::LightBlue,
+        ansi::Color::BrightMagenta => Color::LightMagenta,
+        ansi::Color::BrightCyan => Color::LightCyan,
+        ansi::Color::BrightWhite => Color::White,
+        ansi::Color::Fixed(n) => Color::Indexed(n),
+        ansi::Color::Rgb(r, g, b) if ansi::is_truecolor_enabled() => Color::Rgb(r, g, b),
+        ansi::Color::Rgb(r, g, b) => Color::Indexed(ansi::to_ansi256(r, g, b)),
+    }
+}
@@ -7,3 +7,12 @@ pub use view::*;
 mod output;
 pub use output::*;
 mod inspect;
+pub use inspect::Inspect;
+mod revert;
+pub use revert::*;
+mod diff;
+pub use diff::*;
+mod strings;
+pub use strings::*;
+mod stats;
+pub use stats::*;
@@ -1,7 +1,8 @@
 // Library
-use crate::utils::format::Format;
-use crate::utils::helpers;
 use clap::Parser;
+use hex_ray::utils::format::Format;
+use hex_ray::utils::helpers;
+use hex_ray::Dumper;
 
 // --------------
 // OUTPUT COMMAND
@@ -17,7 +18,9 @@ pub struct Output {
     /// The byte offset at which to start reading; i.e. skip the given number of bytes.
     ///
     /// You can specify a positive or negative integer value; A positive integer offset
-    /// seeks forward from the start, while a negative offset seeks backwards from the end
+    /// seeks forward from the start, while a negative offset seeks backwards from the end.
+    /// (Use `--offset=-N` to pass a negative value.) STDIN is read through to the offset
+    /// instead, keeping only the last N bytes in memory for negative offsets
     #[arg(aliases = ["skip", "seek"], short, long, default_value_t = 0)]
     pub offset: i64,
 
@@ -29,7 +32,9 @@ pub struct Output {
 
     /// The output display format.
     ///
-    /// This can be one of the following: hex (x), HEX (X), binary (b), octal (o), decimal (d).
+    /// This can be one of the following: hex (x), HEX (X), binary (b), octal (o), decimal (d),
+    /// a multi-byte integer: u16, i16, u32, i32, u64, i64, u128, i128, or a floating point
+    /// number: f16, f32, f64 (with an optional `le` or `be` suffix for the byte-order).
     ///
     /// To output with the corresponding prefixes prepend a `#` to the format (e.g. `#hex` or `#x`)
     #[arg(short, long, default_value = "hex")]
@@ -41,36 +46,12 @@ pub struct Output {
 }
 
 impl Output {
-    pub fn execute(self) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn execute(self) -> hex_ray::Result<()> {
         let (reader, _) = helpers::get_reader_and_offset(self.filepath.as_ref(), self.offset)?;
-        Ok(self.dump(reader)?)
-    }
-
-    fn dump<T>(&self, mut data: T) -> Result<(), Box<dyn std::error::Error>>
-    where
-        T: std::io::BufRead,
-    {
-        // Buffer to store the data
-        let mut buffer = vec![0; 16];
-        // The number of bytes remaining to be read
-        let mut bytes_remaining = self.limit.unwrap_or(usize::MAX);
-
-        while bytes_remaining > 0 {
-            // Determine the number of bytes to be read in this iteration
-            let bytes_to_read = std::cmp::min(bytes_remaining, 16);
-
-            let bytes_read = data.read(&mut buffer[0..bytes_to_read])?;
-            if bytes_read == 0 {
-                break;
-            }
-
-            buffer.iter().take(bytes_read).for_each(|b| {
-                let s = self.format.format(*b);
-                print!("{}{}", s, self.separator)
-            });
-
-            bytes_remaining -= bytes_read;
-        }
+        Dumper::new()
+            .format(self.format.clone())
+            .limit(self.limit)
+            .dump_values(reader, &self.separator, &mut std::io::stdout().lock())?;
         Ok(())
     }
 }
@@ -0,0 +1,407 @@
+// Library
+use clap::Parser;
+use hex_ray::utils::encoding::{Encoding, Substitution};
+use hex_ray::utils::format::{Format, Radix};
+use hex_ray::utils::helpers;
+use hex_ray::Error;
+use std::io::{Seek, Write};
+
+// --------------
+// REVERT COMMAND
+// --------------
+
+#[derive(Parser, Clone)]
+#[command(version, about)]
+pub struct Revert {
+    /// Path to the hex-dump to read (defaults to reading from `stdin` if empty)
+    ///
+    /// This can either be the output of `view --plain` or of the `output` command.
+    #[clap(aliases = ["path", "src"])]
+    pub filepath: Option<std::path::PathBuf>,
+
+    /// The display format of the values in the hex-dump.
+    ///
+    /// This can be any of the formats supported by `view` and `output`, except for the
+    /// floating point formats. The prefixes (e.g. `0x`) are optional.
+    #[arg(short, long, default_value = "hex")]
+    pub format: Format,
+
+    /// Character that separates the values in the `output` stream
+    #[arg(short, long, default_value_t = String::from(" "))]
+    pub separator: String,
+
+    /// Path to the file to write the bytes to (defaults to writing to `stdout` if empty)
+    ///
+    /// When patching, this is the existing file to patch and is required.
+    #[arg(alias = "out", long = "output", short = 'O')]
+    pub output: Option<std::path::PathBuf>,
+
+    /// The byte offset at which to start patching the values of an `output` stream.
+    ///
+    /// The lines of a `view --plain` dump are patched at the offsets in their offset column.
+    #[arg(aliases = ["skip", "seek"], short, long, default_value_t = 0)]
+    pub offset: u64,
+
+    /// The radix of the offset column of a `view --plain` dump: hex (x), decimal (d) or octal (o)
+    #[arg(short, long, value_enum, default_value_t)]
+    pub radix: Radix,
+
+    /// The character encoding of the text column of a `view --plain` dump, as passed to `view`.
+    ///
+    /// The text column tells the padding of the last value apart from the zeros in the data.
+    #[arg(short, long, value_enum, default_value_t)]
+    pub encoding: Encoding,
+
+    /// The glyphs for the control characters in the text column of a `view --plain` dump, as
+    /// passed to `view`
+    #[arg(long, value_enum, default_value_t)]
+    pub substitution: Substitution,
+}
+
+/// A chunk of bytes reconstructed from a single line of the hex-dump
+#[derive(Debug, PartialEq)]
+struct Chunk {
+    offset: Option<u64>, // The offset of the bytes, if the line had an offset column
+    bytes: Vec<u8>,      // The reconstructed bytes
+}
+
+impl Chunk {
+    /// Returns the chunk that a `*` line stands in for; this chunk repeated up to the `end` offset
+    fn repeat_until(&self, end: u64) -> Option<Chunk> {
+        let start = self.offset? + self.bytes.len() as u64;
+        let len = end.checked_sub(start)? as usize;
+        Some(Chunk {
+            offset: Some(start),
+            bytes: self.bytes.iter().copied().cycle().take(len).collect(),
+        })
+    }
+}
+
+impl Revert {
+    /// Rebuild the binary data from the hex-dump and write it out
+    pub fn execute(self) -> hex_ray::Result<()> {
+        let (reader, _) = helpers::get_reader_and_offset(self.filepath.as_ref(), 0)?;
+        let chunks = self.parse(reader)?;
+
+        let mut writer: Box<dyn Write> = match &self.output {
+            Some(path) => Box::new(std::io::BufWriter::new(std::fs::File::create(path)?)),
+            None => Box::new(std::io::BufWriter::new(std::io::stdout().lock())),
+        };
+
+        for chunk in chunks {
+            writer.write_all(&chunk.bytes)?;
+        }
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Write the bytes from the hex-dump into an existing file, at the offsets they came from
+    pub fn patch(self) -> hex_ray::Result<()> {
+        let Some(path) = &self.output else {
+            return Err(Error::InvalidArgument(String::from(
+                "A file to patch must be specified with --output",
+            )));
+        };
+
+        let (reader, _) = helpers::get_reader_and_offset(self.filepath.as_ref(), 0)?;
+        let chunks = self.parse(reader)?;
+
+        // Open the existing file without truncating it
+        let mut file = std::fs::OpenOptions::new().write(true).open(path)?;
+
+        // Values from an `output` stream follow one another starting at the given offset
+        let mut position = self.offset;
+        for chunk in chunks {
+            let offset = chunk.offset.unwrap_or(position);
+            file.seek(std::io::SeekFrom::Start(offset))?;
+            file.write_all(&chunk.bytes)?;
+            position = offset + chunk.bytes.len() as u64;
+        }
+        file.flush()?;
+
+        Ok(())
+    }
+
+    /// Parse all the lines in the hex-dump into chunks of bytes
+    fn parse<T>(&self, reader: T) -> hex_ray::Result<Vec<Chunk>>
+    where
+        T: std::io::BufRead,
+    {
+        if let Format::Float(..) = self.format {
+            return Err(Error::InvalidArgument(String::from(
+                "Floating point values are lossy and cannot be reverted",
+            )));
+        }
+
+        let mut chunks: Vec<Chunk> = Vec::new();
+        let mut squeezed = false; // Whether the last line was a `*` standing in for repeated rows
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if line.trim() == "*" {
+                squeezed = true;
+                continue;
+            }
+
+            let chunk = match self.parse_view_line(&line, chunks.last()) {
+                Some(result) => result,
+                None => self.parse_output_line(&line),
+            };
+            let chunk = chunk.map_err(|value| {
+                Error::InvalidInput(format!(
+                    "Invalid {:?} value {:?} on line {}",
+                    self.format,
+                    value,
+                    i + 1
+                ))
+            })?;
+
+            // Fill in the rows that were squeezed, now that the offset they run up to is known
+            if std::mem::take(&mut squeezed) {
+                let repeated = chunks
+                    .last()
+                    .zip(chunk.offset)
+                    .and_then(|(previous, end)| previous.repeat_until(end));
+                chunks.extend(repeated);
+            }
+            chunks.push(chunk);
+        }
+
+        Ok(chunks)
+    }
+
+    /// Parse a line of `view --plain` output (i.e. `offset:  values  | ascii`).
+    ///
+    /// Returns `None` if the line does not have an offset column. Otherwise, returns
+    /// the chunk of bytes or the value that could not be parsed. The `previous` chunk
+    /// completes the characters that cross into the text column of the line.
+    fn parse_view_line(
+        &self,
+        line: &str,
+        previous: Option<&Chunk>,
+    ) -> Option<Result<Chunk, String>> {
+        // The values of an `output` stream may be separated by colons too, so only lines laid out
+        // like the plain view (two spaces after the offset, or nothing after the last offset of
+        // a squeezed dump) are taken for it
+        if self.separator.contains(':') {
+            return None;
+        }
+        let (offset, rest) = line.split_once(':')?;
+        if !rest.is_empty() && !rest.starts_with("  ") {
+            return None;
+        }
+        let offset = self.radix.parse(offset.trim())?;
+
+        // Split off the ASCII gutter
+        let (values, ascii) = match rest.find(" | ") {
+            Some(i) => (&rest[..i], Some(&rest[i + 3..])),
+            None => (rest, None),
+        };
+
+        let mut bytes = match self.parse_values(values.split_whitespace()) {
+            Ok(bytes) => bytes,
+            Err(value) => return Some(Err(value)),
+        };
+
+        // The last value in a row may have been padded out with zeros to fill a whole word.
+        // Render the text column for each length the row could have had, and keep the one
+        // that matches the gutter. The padding never trims off more than the trailing zeros.
+        if let Some(ascii) = ascii {
+            let end = bytes.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+            let min = bytes.len().saturating_sub(self.format.bytes() - 1);
+            let context = previous
+                .filter(|chunk| chunk.offset.map(|o| o + chunk.bytes.len() as u64) == Some(offset))
+                .map_or(&[][..], |chunk| self.context(&chunk.bytes));
+            let len = (end.max(min)..=bytes.len())
+                .rev()
+                .find(|&len| same_text(&self.render_text(context, &bytes[..len]), ascii))
+                .unwrap_or(bytes.len());
+            bytes.truncate(len);
+        }
+
+        Some(Ok(Chunk {
+            offset: Some(offset),
+            bytes,
+        }))
+    }
+
+    /// The bytes at the end of the row before, which the text column decodes along with the row
+    fn context<'a>(&self, bytes: &'a [u8]) -> &'a [u8] {
+        let len = std::cmp::min(bytes.len(), 4);
+        &bytes[bytes.len() - (len - len % self.encoding.unit())..]
+    }
+
+    /// Render the text column of the row like `view` does, given the bytes of the row before it
+    fn render_text(&self, context: &[u8], row: &[u8]) -> String {
+        let window = [context, row].concat();
+        let row = context.len()..window.len();
+        let start = self.encoding.window(row.clone()).start;
+        self.encoding
+            .decode_row(&window[start..], row.start - start..row.end - start)
+            .into_iter()
+            .map(|glyph| self.substitution.render(glyph))
+            .collect()
+    }
+
+    /// Parse a line of values separated by the separator (i.e. the output of the `output` command)
+    fn parse_output_line(&self, line: &str) -> Result<Chunk, String> {
+        let bytes = if self.separator.trim().is_empty() {
+            self.parse_values(line.split_whitespace())?
+        } else {
+            self.parse_values(line.split(self.separator.as_str()))?
+        };
+        Ok(Chunk {
+            offset: None,
+            bytes,
+        })
+    }
+
+    /// Parse the values into bytes, returning the first value that could not be parsed on failure
+    fn parse_values<'a>(&self, values: impl Iterator<Item = &'a str>) -> Result<Vec<u8>, String> {
+        let mut bytes = Vec::new();
+        for value in values.map(str::trim).filter(|v| !v.is_empty()) {
+            match self.format.parse_bytes(value) {
+                Some(b) => bytes.extend(b),
+                None => return Err(value.to_string()),
+            }
+        }
+        Ok(bytes)
+    }
+}
+
+/// Returns true if the two text columns are the same, regardless of the spaces between the groups
+/// and after the last byte
+fn same_text(a: &str, b: &str) -> bool {
+    a.chars()
+        .filter(|c| *c != ' ')
+        .eq(b.chars().filter(|c| *c != ' '))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn revert(format: &str, separator: &str) -> Revert {
+        Revert {
+            filepath: None,
+            format: format.parse().unwrap(),
+            separator: String::from(separator),
+            output: None,
+            offset: 0,
+            radix: Radix::Hex,
+            encoding: Encoding::Ascii,
+            substitution: Substitution::Dot,
+        }
+    }
+
+    #[test]
+    fn should_parse_plain_view_lines() {
+        let cmd = revert("hex", " ");
+        let chunks = cmd
+            .parse("00000020:  48 65 6c 6c  6f 0a               | Hell o·\n".as_bytes())
+            .unwrap();
+        assert_eq!(
+            chunks,
+            vec![Chunk {
+                offset: Some(0x20),
+                bytes: b"Hello\n".to_vec()
+            }]
+        );
+    }
+
+    #[test]
+    fn should_trim_padding_from_partial_words() {
+        let cmd = revert("u32be", " ");
+        let line = "00000000:    1214606444    1862926336   | Hell o·\n";
+        let chunks = cmd.parse(line.as_bytes()).unwrap();
+        assert_eq!(chunks[0].bytes, b"Hello\n".to_vec());
+    }
+
+    #[test]
+    fn should_keep_spaces_when_trimming_padding() {
+        let cmd = revert("u32be", " ");
+        let line = "00000000:    1214606444    1864398703    1919706112   | Hell o wo rld \n";
+        let chunks = cmd.parse(line.as_bytes()).unwrap();
+        assert_eq!(chunks[0].bytes, b"Hello world".to_vec());
+    }
+
+    #[test]
+    fn should_trim_padding_with_any_text_column() {
+        let data = "h\u{e9}llo \x01w\u{20ac}rld\x1b\x00x\x00".as_bytes();
+        let cases = [
+            (Encoding::Ascii, Substitution::Caret),
+            (Encoding::Ascii, Substitution::Pictures),
+            (Encoding::Utf8, Substitution::Dot),
+            (Encoding::Utf8, Substitution::Caret),
+            (Encoding::Utf8, Substitution::Pictures),
+        ];
+        for (encoding, substitution) in cases {
+            let mut dump = Vec::new();
+            hex_ray::Dumper::new()
+                .size(6)
+                .format("u32be".parse().unwrap())
+                .color(false)
+                .encoding(encoding)
+                .substitution(substitution)
+                .plain(true)
+                .dump(data, 0, &mut dump)
+                .unwrap();
+
+            let cmd = Revert {
+                encoding,
+                substitution,
+                ..revert("u32be", " ")
+            };
+            let chunks = cmd.parse(dump.as_slice()).unwrap();
+            let bytes: Vec<u8> = chunks.into_iter().flat_map(|c| c.bytes).collect();
+            assert_eq!(bytes, data, "{:?} {:?}", encoding, substitution);
+        }
+    }
+
+    #[test]
+    fn should_expand_squeezed_rows() {
+        let cmd = revert("hex", " ");
+        let dump = "00000000:  61 62   | ab\n*\n00000007:  63      | c \n";
+        let chunks = cmd.parse(dump.as_bytes()).unwrap();
+        let bytes: Vec<u8> = chunks.into_iter().flat_map(|c| c.bytes).collect();
+        assert_eq!(bytes, b"abababac".to_vec());
+    }
+
+    #[test]
+    fn should_parse_output_streams() {
+        let cmd = revert("#bin", ",");
+        let chunks = cmd.parse("0b01001000,0b01101001,".as_bytes()).unwrap();
+        assert_eq!(
+            chunks,
+            vec![Chunk {
+                offset: None,
+                bytes: b"Hi".to_vec()
+            }]
+        );
+    }
+
+    #[test]
+    fn should_parse_output_streams_separated_by_colons() {
+        for (format, data) in [("hex", &b"Hello"[..]), ("u32be", &b"Hello, world"[..])] {
+            let mut stream = Vec::new();
+            hex_ray::Dumper::new()
+                .format(format.parse().unwrap())
+                .dump_values(data, ":", &mut stream)
+                .unwrap();
+
+            let chunks = revert(format, ":").parse(stream.as_slice()).unwrap();
+            let bytes: Vec<u8> = chunks.into_iter().flat_map(|c| c.bytes).collect();
+            assert_eq!(bytes, data, "{}", format);
+        }
+    }
+
+    #[test]
+    fn should_report_invalid_values() {
+        let cmd = revert("hex", " ");
+        let err = cmd.parse("48 65\n6c zz".as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("\"zz\" on line 2"));
+    }
+}
@@ -0,0 +1,384 @@
+// Library
+use clap::Parser;
+use hex_ray::utils::{format::Radix, helpers};
+use std::io::{Read, Write};
+
+/// The number of bytes to read at a time
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The width of the entropy bars of the blocks
+const BAR_WIDTH: usize = 32;
+
+// -------------
+// STATS COMMAND
+// -------------
+
+#[derive(Parser, Clone)]
+#[command(version, about)]
+pub struct Stats {
+    /// Path to the file to read (defaults to reading from `stdin` if empty)
+    #[clap(aliases = ["path", "src"])]
+    pub filepath: Option<std::path::PathBuf>,
+
+    /// The byte offset at which to start reading; i.e. skip the given number of bytes.
+    ///
+    /// You can specify a positive or negative integer value; A positive integer offset
+    /// seeks forward from the start, while a negative offset seeks backwards from the end.
+    /// (Use `--offset=-N` to pass a negative value.) STDIN is read through to the offset
+    /// instead, keeping only the last N bytes in memory for negative offsets
+    #[arg(aliases = ["skip", "seek"], short, long, default_value_t = 0)]
+    pub offset: i64,
+
+    /// The number of bytes to read.
+    ///
+    /// The program will stop after reading the specified number of bytes.
+    #[arg(short, long)]
+    pub limit: Option<usize>,
+
+    /// The size of the blocks whose entropy is reported on its own
+    #[arg(short, long, value_parser = crate::cli::parse_count, default_value_t = 64 * 1024)]
+    pub block_size: usize,
+
+    /// The number of the most common byte values to report
+    #[arg(short = 'n', long, default_value_t = 10)]
+    pub top: usize,
+
+    /// The radix of the offsets of the blocks: hex (x), decimal (d) or octal (o)
+    #[arg(short, long, value_enum, default_value_t)]
+    pub radix: Radix,
+
+    /// Output the report as a JSON object instead of the tables
+    #[arg(short, long)]
+    pub json: bool,
+
+    /// Leave out the entropy of each block, and only report on the input as a whole
+    #[arg(short, long)]
+    pub summary: bool,
+}
+
+/// The number of times each byte value occurs
+type Histogram = [u64; 256];
+
+/// The byte statistics of a block of the data
+struct Block {
+    offset: usize, // The position of the first byte of the block
+    len: usize,    // The number of bytes in the block
+    entropy: f64,  // The Shannon entropy of the block, in bits per byte
+}
+
+impl Stats {
+    pub fn execute(self) -> hex_ray::Result<()> {
+        let (reader, offset) = helpers::get_reader_and_offset(self.filepath.as_ref(), self.offset)?;
+        let reader = reader.take(self.limit.map_or(u64::MAX, |limit| limit as u64));
+        let mut writer = std::io::BufWriter::new(std::io::stdout().lock());
+        self.report(reader, offset, &mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Stream through the input, counting the bytes of the whole input and of each block.
+    ///
+    /// The blocks are written out as they complete, ahead of the totals, so that none of them
+    /// are held in memory
+    fn report<R: Read, W: Write>(
+        &self,
+        mut reader: R,
+        offset: usize,
+        writer: &mut W,
+    ) -> hex_ray::Result<()> {
+        if !self.json {
+            match &self.filepath {
+                Some(filepath) => writeln!(writer, "Source:     {}", filepath.to_string_lossy())?,
+                None => writeln!(writer, "Source:     STDIN")?,
+            }
+        }
+        if !self.summary {
+            match self.json {
+                true => write!(writer, r#"{{"block_size":{},"blocks":["#, self.block_size)?,
+                false => writeln!(writer, "\nEntropy per block ({} bytes)", self.block_size)?,
+            }
+        }
+
+        let offset_width = self.radix.width(self.max_offset(offset) as u64);
+        let mut histogram = [0; 256];
+        let mut block_histogram = [0; 256];
+        let mut block_len = 0;
+        let mut total = 0;
+        let mut buffer = vec![0; CHUNK_SIZE];
+        loop {
+            let bytes_read = helpers::read_chunk(&mut reader, &mut buffer)?;
+            if bytes_read == 0 {
+                break;
+            }
+            for &byte in &buffer[..bytes_read] {
+                histogram[byte as usize] += 1;
+                block_histogram[byte as usize] += 1;
+                block_len += 1;
+                total += 1;
+                if block_len == self.block_size {
+                    let block = Block {
+                        offset: offset + total - block_len,
+                        len: block_len,
+                        entropy: entropy(&block_histogram),
+                    };
+                    self.write_block(writer, &block, offset, offset_width)?;
+                    block_histogram = [0; 256];
+                    block_len = 0;
+                }
+            }
+        }
+        if block_len > 0 {
+            let block = Block {
+                offset: offset + total - block_len,
+                len: block_len,
+                entropy: entropy(&block_histogram),
+            };
+            self.write_block(writer, &block, offset, offset_width)?;
+        }
+
+        match (self.json, self.summary) {
+            (true, true) => writeln!(writer, "{{{}}}", self.format_json(&histogram, offset))?,
+            (true, false) => writeln!(writer, "],{}}}", self.format_json(&histogram, offset))?,
+            (false, _) => self.write_report(writer, &histogram)?,
+        }
+        Ok(())
+    }
+
+    /// The largest offset of the blocks, as far as it is known before reading, which the offset
+    /// column is sized to fit
+    fn max_offset(&self, offset: usize) -> usize {
+        let len = self
+            .filepath
+            .as_ref()
+            .and_then(|filepath| std::fs::metadata(filepath).ok())
+            .filter(std::fs::Metadata::is_file)
+            .map(|metadata| metadata.len() as usize);
+        let end = self.limit.map(|limit| offset.saturating_add(limit));
+        [len, end].into_iter().flatten().min().unwrap_or(offset)
+    }
+
+    /// Write out the entropy of a block (unless only the summary is reported), with a bar scaled
+    /// to the maximum of 8 bits per byte
+    fn write_block<W: Write>(
+        &self,
+        writer: &mut W,
+        block: &Block,
+        offset: usize,
+        offset_width: usize,
+    ) -> std::io::Result<()> {
+        if self.summary {
+            return Ok(());
+        }
+        if self.json {
+            let separator = if block.offset == offset { "" } else { "," };
+            return write!(
+                writer,
+                r#"{}{{"offset":{},"length":{},"entropy":{:.6}}}"#,
+                separator, block.offset, block.len, block.entropy
+            );
+        }
+        let bar = (block.entropy / 8.0 * BAR_WIDTH as f64).round() as usize;
+        writeln!(
+            writer,
+            "  {:0>width$}  {:.4}  {}",
+            self.radix.format(block.offset as u64),
+            block.entropy,
+            "█".repeat(bar),
+            width = offset_width
+        )
+    }
+
+    /// Write the totals of the report as tables
+    fn write_report<W: Write>(&self, writer: &mut W, histogram: &Histogram) -> std::io::Result<()> {
+        let total: u64 = histogram.iter().sum();
+        let percent = |count: u64| match total {
+            0 => 0.0,
+            _ => count as f64 * 100.0 / total as f64,
+        };
+        let (zero, printable, high) = classify(histogram);
+
+        if !self.summary {
+            writeln!(writer)?;
+        }
+        writeln!(writer, "Bytes:      {}", total)?;
+        writeln!(writer, "Entropy:    {:.4} bits/byte", entropy(histogram))?;
+        writeln!(writer, "Zero:       {} ({:.2}%)", zero, percent(zero))?;
+        writeln!(
+            writer,
+            "Printable:  {} ({:.2}%)",
+            printable,
+            percent(printable)
+        )?;
+        writeln!(writer, "High:       {} ({:.2}%)", high, percent(high))?;
+
+        // The most common byte values
+        writeln!(writer, "\nMost common bytes")?;
+        for (byte, count) in most_common(histogram, self.top) {
+            writeln!(
+                writer,
+                "  {:02x}  {:>12}  {:>6.2}%",
+                byte,
+                count,
+                percent(count)
+            )?;
+        }
+
+        // The histogram, with the rows by the high nibble and the columns by the low nibble
+        let width = histogram
+            .iter()
+            .max()
+            .map_or(1, |max| max.to_string().len());
+        writeln!(writer, "\nHistogram")?;
+        write!(writer, "    ")?;
+        for low in 0..16 {
+            write!(writer, " {:>width$x}", low)?;
+        }
+        writeln!(writer)?;
+        for (high, row) in histogram.chunks(16).enumerate() {
+            write!(writer, "  {:x}_", high)?;
+            for count in row {
+                write!(writer, " {:>width$}", count)?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Format the totals of the report as the fields of a JSON object
+    fn format_json(&self, histogram: &Histogram, offset: usize) -> String {
+        let total: u64 = histogram.iter().sum();
+        let (zero, printable, high) = classify(histogram);
+        let most_common: Vec<String> = most_common(histogram, self.top)
+            .into_iter()
+            .map(|(byte, count)| format!(r#"{{"byte":{},"count":{}}}"#, byte, count))
+            .collect();
+        let counts: Vec<String> = histogram.iter().map(u64::to_string).collect();
+        format!(
+            r#""offset":{},"length":{},"entropy":{:.6},"zero":{},"printable":{},"high":{},"most_common":[{}],"histogram":[{}]"#,
+            offset,
+            total,
+            entropy(histogram),
+            zero,
+            printable,
+            high,
+            most_common.join(","),
+            counts.join(",")
+        )
+    }
+}
+
+/// Returns the Shannon entropy of the bytes counted in the histogram, in bits per byte
+/// (from 0 for a single repeated value, to 8 for uniformly random bytes)
+fn entropy(histogram: &Histogram) -> f64 {
+    let total: u64 = histogram.iter().sum();
+    if total == 0 {
+        return 0.0;
+    }
+    let entropy: f64 = histogram
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / total as f64;
+            -p * p.log2()
+        })
+        .sum();
+    // A single repeated value sums up to -0.0, which would be printed with its sign
+    entropy.abs()
+}
+
+/// Returns the number of zero, printable ASCII and high (`0x80` and above) bytes
+fn classify(histogram: &Histogram) -> (u64, u64, u64) {
+    let printable = (0..=255u8)
+        .filter(helpers::is_printable_ascii_character)
+        .map(|byte| histogram[byte as usize])
+        .sum();
+    let high = histogram[0x80..].iter().sum();
+    (histogram[0], printable, high)
+}
+
+/// Returns the `n` most common byte values that occur, along with their counts
+fn most_common(histogram: &Histogram, n: usize) -> Vec<(u8, u64)> {
+    let mut counts: Vec<(u8, u64)> = (0..=255u8)
+        .map(|byte| (byte, histogram[byte as usize]))
+        .filter(|&(_, count)| count > 0)
+        .collect();
+    // Order by the count, and then by the byte value for equal counts
+    counts.sort_by_key(|&(byte, count)| (std::cmp::Reverse(count), byte));
+    counts.truncate(n);
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn histogram(data: &[u8]) -> Histogram {
+        let mut histogram = [0; 256];
+        for &byte in data {
+            histogram[byte as usize] += 1;
+        }
+        histogram
+    }
+
+    #[test]
+    fn should_measure_the_entropy() {
+        assert_eq!(entropy(&histogram(b"")), 0.0);
+        assert_eq!(entropy(&histogram(b"aaaa")), 0.0);
+        assert_eq!(entropy(&histogram(b"abab")), 1.0);
+        assert_eq!(entropy(&histogram(b"abcd")), 2.0);
+        let all: Vec<u8> = (0..=255).collect();
+        assert_eq!(entropy(&histogram(&all)), 8.0);
+    }
+
+    #[test]
+    fn should_classify_and_rank_the_bytes() {
+        let histogram = histogram(b"\x00\x00hello\xff\x80\n");
+        assert_eq!(classify(&histogram), (2, 5, 2));
+        assert_eq!(
+            most_common(&histogram, 3),
+            vec![(0x00, 2), (b'l', 2), (b'\n', 1)]
+        );
+    }
+
+    fn report(data: &[u8], json: bool, summary: bool) -> String {
+        let stats = Stats {
+            filepath: None,
+            offset: 0,
+            limit: None,
+            block_size: 4,
+            top: 1,
+            radix: Radix::Hex,
+            json,
+            summary,
+        };
+        let mut output = Vec::new();
+        stats.report(data, 16, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn should_write_the_blocks_ahead_of_the_totals() {
+        let output = report(b"aaaaabab", false, false);
+        let lines: Vec<&str> = output.lines().take(6).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Source:     STDIN",
+                "",
+                "Entropy per block (4 bytes)",
+                "  00000010  0.0000  ",
+                "  00000014  1.0000  ████",
+                "",
+            ]
+        );
+        assert!(!report(b"aaaaabab", false, true).contains("Entropy per block"));
+
+        let json = report(b"aaaaabab", true, false);
+        assert!(json.starts_with(
+            r#"{"block_size":4,"blocks":[{"offset":16,"length":4,"entropy":0.000000},{"offset":20,"length":4,"entropy":1.000000}],"offset":16,"length":8,"#
+        ));
+        let json = report(b"aaaaabab", true, true);
+        assert!(json.starts_with(r#"{"offset":16,"length":8,"#));
+        assert!(json.trim_end().ends_with("]}"));
+    }
+}
@@ -0,0 +1,133 @@
+// Library
+use clap::Parser;
+use hex_ray::strings::Found;
+use hex_ray::utils::{format::Radix, helpers};
+use hex_ray::{Scanner, StringEncoding};
+use std::io::Write;
+
+// ---------------
+// STRINGS COMMAND
+// ---------------
+
+#[derive(Parser, Clone)]
+#[command(version, about)]
+pub struct Strings {
+    /// Path to the file to read (defaults to reading from `stdin` if empty)
+    #[clap(aliases = ["path", "src"])]
+    pub filepath: Option<std::path::PathBuf>,
+
+    /// The byte offset at which to start reading; i.e. skip the given number of bytes.
+    ///
+    /// You can specify a positive or negative integer value; A positive integer offset
+    /// seeks forward from the start, while a negative offset seeks backwards from the end.
+    /// (Use `--offset=-N` to pass a negative value.) STDIN is read through to the offset
+    /// instead, keeping only the last N bytes in memory for negative offsets
+    #[arg(aliases = ["skip", "seek"], short, long, default_value_t = 0)]
+    pub offset: i64,
+
+    /// The number of bytes to read.
+    ///
+    /// The program will stop after reading the specified number of bytes.
+    #[arg(short, long)]
+    pub limit: Option<usize>,
+
+    /// The minimum number of characters in a run of text
+    #[arg(short = 'n', long, value_parser = crate::cli::parse_count, default_value_t = 4)]
+    pub min_len: usize,
+
+    /// The encodings to look for, separated by commas: ascii, utf8 (which includes ascii),
+    /// utf16le or utf16be. UTF-16 runs are made up of the characters in the Latin-1 range
+    #[arg(short, long, value_enum, value_delimiter = ',', default_values_t = [StringEncoding::Utf8, StringEncoding::Utf16le])]
+    pub encoding: Vec<StringEncoding>,
+
+    /// The radix of the offsets: hex (x), decimal (d) or octal (o)
+    #[arg(short, long, value_enum, default_value_t)]
+    pub radix: Radix,
+
+    /// Output a JSON object for each run of text, one per line
+    #[arg(short, long)]
+    pub json: bool,
+}
+
+impl Strings {
+    pub fn execute(self) -> hex_ray::Result<()> {
+        let (reader, offset) = helpers::get_reader_and_offset(self.filepath.as_ref(), self.offset)?;
+
+        // Pad the offsets to fit the last offset, as in the offset column of the hex-dump
+        let len = match &self.filepath {
+            Some(path) => {
+                let available = (std::fs::metadata(path)?.len() as usize).saturating_sub(offset);
+                self.limit
+                    .map_or(available, |limit| std::cmp::min(limit, available))
+            }
+            None => self.limit.unwrap_or(0),
+        };
+        let width = self.radix.width((offset + len) as u64);
+
+        let mut writer = std::io::BufWriter::new(std::io::stdout().lock());
+        Scanner::new(&self.encoding, self.min_len, offset).scan(reader, self.limit, |found| {
+            match self.json {
+                true => writeln!(writer, "{}", format_json(&found))?,
+                false => writeln!(writer, "{}", self.format_line(&found, width))?,
+            }
+            Ok(())
+        })?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Format the run as a line with its offset and encoding
+    fn format_line(&self, found: &Found, width: usize) -> String {
+        format!(
+            "{:0>width$}  {:<7}  {}",
+            self.radix.format(found.offset as u64),
+            found.encoding.name(),
+            found.text,
+        )
+    }
+}
+
+/// Format the run as a JSON object
+fn format_json(found: &Found) -> String {
+    format!(
+        "{{\"offset\":{},\"length\":{},\"encoding\":\"{}\",\"text\":\"{}\"}}",
+        found.offset,
+        found.len,
+        found.encoding.name(),
+        escape_json(&found.text),
+    )
+}
+
+/// Escape the text for a JSON string
+fn escape_json(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_format_runs_as_json() {
+        let found = Found {
+            offset: 16,
+            len: 8,
+            encoding: StringEncoding::Utf16le,
+            text: String::from("a\t\"b\\"),
+        };
+        assert_eq!(
+            format_json(&found),
+            r#"{"offset":16,"length":8,"encoding":"utf16le","text":"a\t\"b\\"}"#
+        );
+    }
+}
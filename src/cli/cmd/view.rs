@@ -1,10 +1,13 @@
 // Library
-use crate::utils::{
-    ansi::{Color, Colorable},
-    format::Format,
+use clap::Parser;
+use hex_ray::utils::{
+    ansi,
+    encoding::{Encoding, Substitution},
+    format::{Format, Radix},
     helpers,
+    theme::Palette,
 };
-use clap::Parser;
+use hex_ray::Dumper;
 
 // ------------
 // VIEW COMMAND
@@ -20,7 +23,9 @@ pub struct View {
     /// The byte offset at which to start reading; i.e. skip the given number of bytes.
     ///
     /// You can specify a positive or negative integer value; A positive integer offset
-    /// seeks forward from the start, while a negative offset seeks backwards from the end
+    /// seeks forward from the start, while a negative offset seeks backwards from the end.
+    /// (Use `--offset=-N` to pass a negative value.) STDIN is read through to the offset
+    /// instead, keeping only the last N bytes in memory for negative offsets
     #[arg(aliases = ["skip", "seek"], short, long, default_value_t = 0)]
     pub offset: i64,
 
@@ -31,82 +36,128 @@ pub struct View {
     pub limit: Option<usize>,
 
     /// The size of each row
-    #[arg(short, long, default_value_t = 16)]
+    #[arg(short, long, value_parser = crate::cli::parse_count, default_value_t = 16)]
     pub size: usize,
 
     /// The output display format.
     ///
-    /// This can be one of the following: hex (x), HEX (X), binary (b), octal (o), decimal (d).
+    /// This can be one of the following: hex (x), HEX (X), binary (b), octal (o), decimal (d),
+    /// a multi-byte integer: u16, i16, u32, i32, u64, i64, u128, i128, or a floating point
+    /// number: f16, f32, f64 (with an optional `le` or `be` suffix for the byte-order).
     ///
     /// To output with the corresponding prefixes prepend a `#` to the format (e.g. `#hex` or `#x`)
     #[arg(short, long, default_value = "hex")]
     pub format: Format,
 
     /// Chunk the output into groups of this size
-    #[arg(alias = "chunk", short, long, default_value_t = 4)]
+    #[arg(alias = "chunk", short, long, value_parser = crate::cli::parse_count, default_value_t = 4)]
     pub group_size: usize,
 
+    /// The radix of the offset column: hex (x), decimal (d) or octal (o)
+    #[arg(short, long, value_enum, default_value_t)]
+    pub radix: Radix,
+
+    /// The number of digits in the offset column (determined from the size of the input)
+    #[arg(skip)]
+    pub offset_width: usize,
+
     /// Disable ANSI colors
     #[arg(short, long)]
     pub no_color: bool,
 
+    /// The color theme, which colors each class of bytes (NUL, printable, whitespace, control,
+    /// high-bit and 0xFF) differently: default, mono, pastel, gruvbox, solarized, or one of the
+    /// themes in the config file. Truecolor themes fall back to the 256-color palette unless
+    /// `COLORTERM` advertises truecolor support
+    #[arg(short, long, default_value = "default")]
+    pub theme: String,
+
+    /// The colors of the theme
+    #[arg(skip)]
+    pub palette: Palette,
+
+    /// The character encoding of the text column: ascii, latin1, cp437, cp037 (EBCDIC), cp1047,
+    /// utf8, utf16le or utf16be. Multi-byte characters span the cells of their bytes
+    #[arg(short, long, value_enum, default_value_t)]
+    pub encoding: Encoding,
+
+    /// The glyphs that stand in for the control characters in the text column: a dot, the
+    /// Unicode control pictures (e.g. ␀, ␛) or the caret notation (e.g. ^@, ^[), which takes up
+    /// two columns for every byte
+    #[arg(long, value_enum, default_value_t)]
+    pub substitution: Substitution,
+
     /// Simple Output
     #[arg(alias = "plain", short = 'p', long)]
     pub simple: bool,
+
+    /// Collapse runs of identical rows into a single `*` line (the default for the plain output)
+    #[arg(short = 'z', long, conflicts_with = "no_squeeze")]
+    pub squeeze: bool,
+
+    /// Show every row, even when it repeats the row before it
+    #[arg(short = 'v', long)]
+    pub no_squeeze: bool,
 }
 
 impl View {
     /// Perform initialization setup
-    pub fn init(&self) -> &Self {
+    pub fn init(&mut self) -> hex_ray::Result<&Self> {
         // Disable ANSI colors by setting the `NO_COLOR` env variable
         if self.no_color || self.simple {
             std::env::set_var("NO_COLOR", "true");
         }
-        self
+        self.palette = crate::cli::config::get().palette(&self.theme)?;
+        Ok(self)
     }
 
-    pub fn execute(self) -> Result<(), Box<dyn std::error::Error>> {
-        self.init();
+    pub fn execute(mut self) -> hex_ray::Result<()> {
+        self.init()?;
         let (reader, offset) = helpers::get_reader_and_offset(self.filepath.as_ref(), self.offset)?;
-        Ok(self.dump(reader, offset)?)
-    }
-
-    /// Print out the hex-dump of the given byte data
-    fn dump<T>(&self, mut data: T, offset: usize) -> Result<(), Box<dyn std::error::Error>>
-    where
-        T: std::io::Read,
-    {
-        // Buffer to store the data
-        let mut buffer = vec![0; self.size];
-
-        // The total number of bytes read already
-        let mut total_bytes_read = 0;
-        // The number of bytes remaining to be read
-        let mut bytes_remaining = self.limit.unwrap_or(usize::MAX);
-
-        self.print_file_name();
-        self.print_top_line();
 
-        while bytes_remaining > 0 {
-            // Determine the number of bytes to be read in this iteration
-            let bytes_to_read = std::cmp::min(bytes_remaining, self.size);
-
-            let bytes_read = data.read(&mut buffer[0..bytes_to_read])?;
-            if bytes_read == 0 {
-                break;
+        // Widen the offset column to fit the last offset. The size of STDIN is not known
+        // upfront, so only the limit (if any) is taken into account there
+        let len = match &self.filepath {
+            Some(path) => {
+                let available = (std::fs::metadata(path)?.len() as usize).saturating_sub(offset);
+                self.limit
+                    .map_or(available, |limit| std::cmp::min(limit, available))
             }
+            None => self.limit.unwrap_or(0),
+        };
+        self.offset_width = self.radix.width((offset + len) as u64);
 
-            self.print_line(&buffer, bytes_read, offset + total_bytes_read);
-            total_bytes_read += bytes_read;
-            bytes_remaining -= bytes_read;
-        }
-
-        self.print_bottom_line();
+        self.print_file_name();
+        let total_bytes_read = self
+            .dumper()
+            .dump(reader, offset, &mut std::io::stdout().lock())?;
         self.print_total(total_bytes_read);
 
         Ok(())
     }
 
+    /// The dumper that lays out the hex-dump table as configured
+    pub fn dumper(&self) -> Dumper {
+        Dumper::new()
+            .size(self.size)
+            .group_size(self.group_size)
+            .format(self.format.clone())
+            .radix(self.radix)
+            .offset_width(self.offset_width)
+            .limit(self.limit)
+            .color(ansi::is_color_enabled())
+            .palette(self.palette)
+            .encoding(self.encoding)
+            .substitution(self.substitution)
+            .plain(self.simple)
+            .squeeze(self.should_squeeze())
+    }
+
+    /// Whether runs of identical rows are collapsed; by default only in the plain output
+    pub fn should_squeeze(&self) -> bool {
+        !self.no_squeeze && (self.squeeze || self.simple)
+    }
+
     fn print_file_name(&self) {
         if !self.simple {
             if let Some(filepath) = &self.filepath {
@@ -117,146 +168,6 @@ impl View {
         }
     }
 
-    fn print_top_line(&self) {
-        if self.simple {
-            return;
-        }
-
-        let mut line = String::from("┌─");
-        line.push_str(&"─".repeat(8 + 2));
-        line.push_str("┬─");
-
-        for i in 0..self.size {
-            if i > 0 && i % self.group_size == 0 {
-                line.push_str("─");
-            }
-            line.push_str(&"─".repeat(self.format.size() + 1));
-        }
-
-        line.push_str("─┬─");
-
-        for i in 0..self.size {
-            if i > 0 && i % self.group_size == 0 {
-                line.push_str("─");
-            }
-            line.push_str("─");
-        }
-
-        line.push_str("─┐");
-        println!("{}", line);
-    }
-
-    /// Prints a row in the hexdump table
-    pub fn print_line(&self, buffer: &[u8], bytes_read: usize, total_bytes_read: usize) {
-        let offset = self.format_offset(total_bytes_read);
-        let hex_values = self.format_hex_values(&buffer, bytes_read);
-        let ascii_values = self.format_ascii_representation(&buffer, bytes_read);
-        if self.simple {
-            println!("{}:  {}  | {}", offset, hex_values, ascii_values);
-        } else {
-            println!("│ {} │ {} │ {} │", offset, hex_values, ascii_values);
-        }
-    }
-
-    /// Print the offset column
-    fn format_offset(&self, offset: usize) -> String {
-        let res = Format::Octal.format(offset as u8);
-        if res.len() > 8 || self.simple {
-            return format!("{:0>8}", res);
-        }
-
-        let mut padding = String::from(" ");
-        for _ in 0..(8 - res.len()) {
-            padding.push_str(&"·".ansi(Color::Black));
-        }
-
-        format!("{}{}", padding, res.ansi(Color::White))
-    }
-
-    /// Print the hex-values columns
-    fn format_hex_values(&self, chunk: &[u8], bytes_read: usize) -> String {
-        let mut s = String::new();
-        // Print the hex values
-        for (j, byte) in chunk.iter().take(bytes_read).enumerate() {
-            // Group values by applying spacing
-            if j > 0 && j % self.group_size == 0 {
-                s.push_str(" ");
-            }
-            let value = self.format.format(*byte);
-            s.push_str(&value.ansi(Color::White)); // Format each byte as a 2-wide hexadecimal value
-            s.push_str(" ");
-        }
-
-        // Print spacing if the chunk is less than size bytes
-        for k in bytes_read..chunk.len() {
-            // Group values by applying spacing
-            if k > 0 && k % self.group_size == 0 {
-                s.push_str(" ");
-            }
-
-            s.push_str(&" ".repeat(self.format.size() + 1)); // Each missing byte is represented by 3 spaces (two for hex-digits and one space)
-        }
-
-        s
-    }
-
-    /// Print the ASCII columns
-    fn format_ascii_representation(&self, chunk: &[u8], bytes_read: usize) -> String {
-        let mut s = String::new();
-
-        // Print the ASCII representation
-        for (k, byte) in chunk.iter().enumerate() {
-            // Group characters by applying spacing
-            if k > 0 && k % self.group_size == 0 {
-                s.push_str(" ");
-            }
-
-            // If there are still bytes to read, print the ASCII character...
-            if k < bytes_read {
-                let c = if helpers::is_printable_ascii_character(&byte) {
-                    let char = (*byte as char).to_string();
-                    format!("{}", char.ansi(Color::White))
-                } else {
-                    format!("{}", "·".ansi(Color::Black)) // Non-printable ASCII characters are replaced by a dot
-                };
-                s.push_str(c.as_str());
-            } else {
-                s.push_str(" "); // Else if there are no more bytes left in this iteration, just print an empty space
-            }
-        }
-
-        s
-    }
-
-    fn print_bottom_line(&self) {
-        if self.simple {
-            return;
-        }
-
-        let mut line = String::from("└─");
-        line.push_str(&"─".repeat(8 + 2));
-        line.push_str("┴─");
-
-        for i in 0..self.size {
-            if i > 0 && i % self.group_size == 0 {
-                line.push_str("─");
-            }
-            line.push_str(&"─".repeat(self.format.size() + 1));
-        }
-
-        line.push_str("─┴─");
-
-        for i in 0..self.size {
-            if i > 0 && i % self.group_size == 0 {
-                line.push_str("─");
-            }
-            line.push_str("─");
-        }
-
-        line.push_str("─┘");
-        println!("{}", line);
-    }
-
     fn print_total(&self, n: usize) {
         if self.simple {
             return;
@@ -0,0 +1,389 @@
+// Library
+use clap::ValueEnum;
+use hex_ray::utils::{
+    ansi::Color,
+    theme::{Palette, Theme},
+};
+use hex_ray::Error;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// The name of the directory in the user's config directory that holds the config file
+const CONFIG_DIR: &str = "hex-ray";
+
+/// The name of the config file
+const CONFIG_FILE: &str = "config.toml";
+
+/// The config that was loaded at startup
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+// -----------
+// CONFIG FILE
+// -----------
+
+/// The contents of the config file
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub view: toml::Table, // The default values of the `view` (and `inspect`) options
+    pub output: toml::Table, // The default values of the `output` options
+    pub themes: HashMap<String, ThemeConfig>, // The named color themes
+    pub keys: HashMap<String, Keys>, // The key bindings of `inspect`, by action
+}
+
+/// A color theme from the config file. Colors that are not set are taken from the base theme
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+    base: Option<String>, // The theme to start from (defaults to the default theme)
+    null: Option<ColorValue>,
+    printable: Option<ColorValue>,
+    whitespace: Option<ColorValue>,
+    control: Option<ColorValue>,
+    high: Option<ColorValue>,
+    ff: Option<ColorValue>,
+    selected: Option<ColorValue>,
+    matched: Option<ColorValue>,
+    range: Option<ColorValue>,
+    modified: Option<ColorValue>,
+    different: Option<ColorValue>,
+}
+
+/// A color; either an index into the 256-color palette, or a name or hex code
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum ColorValue {
+    Index(u8),
+    Name(String),
+}
+
+/// The keys bound to an action; either a single key or a list of them
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum Keys {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Keys {
+    /// The list of keys
+    pub fn to_vec(&self) -> Vec<String> {
+        match self {
+            Keys::One(key) => vec![key.clone()],
+            Keys::Many(keys) => keys.clone(),
+        }
+    }
+}
+
+impl Config {
+    /// Parse the contents of a config file
+    pub fn parse(s: &str) -> Result<Self, String> {
+        toml::from_str(s).map_err(|e| e.message().to_string())
+    }
+
+    /// Read the config file at the given path, or at the default location if there is none.
+    ///
+    /// A missing file at the default location just leaves everything at its defaults.
+    pub fn load(path: Option<&Path>) -> hex_ray::Result<Self> {
+        let (path, explicit) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => match default_path() {
+                Some(path) => (path, false),
+                None => return Ok(Config::default()),
+            },
+        };
+
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if !explicit && e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Config::default())
+            }
+            Err(e) => return Err(Error::InvalidConfig(format!("{}: {}", path.display(), e))),
+        };
+        Self::parse(&contents)
+            .map_err(|e| Error::InvalidConfig(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Supply the values of the config file as the default values of the arguments of the command,
+    /// so that the arguments passed on the command line still take precedence
+    pub fn apply(&self, mut command: clap::Command) -> hex_ray::Result<clap::Command> {
+        let sections = [
+            ("view", &self.view),
+            ("inspect", &self.view),
+            ("output", &self.output),
+        ];
+        for (name, table) in sections {
+            for (key, value) in table {
+                let subcommand = command
+                    .find_subcommand(name)
+                    .expect("the section is named after a subcommand");
+                let Some(id) = find_option(subcommand, key) else {
+                    return Err(Error::InvalidConfig(format!(
+                        "Unknown option {:?} in the [{}] section",
+                        key, name
+                    )));
+                };
+                let value = match value {
+                    toml::Value::String(s) => s.clone(),
+                    toml::Value::Integer(_) | toml::Value::Float(_) | toml::Value::Boolean(_) => {
+                        value.to_string()
+                    }
+                    _ => {
+                        return Err(Error::InvalidConfig(format!(
+                        "The option {:?} in the [{}] section must be a string, number or boolean",
+                        key, name
+                    )))
+                    }
+                };
+                command =
+                    command.mut_subcommand(name, |c| c.mut_arg(id, |a| set_default(a, value)));
+            }
+        }
+        Ok(command)
+    }
+
+    /// Returns the palette of the named theme; either one of the themes from the config file,
+    /// or one of the built-in themes
+    pub fn palette(&self, name: &str) -> hex_ray::Result<Palette> {
+        self.resolve_palette(name, 0)
+    }
+
+    /// Resolve the palette of the named theme, along with the themes it is based on
+    fn resolve_palette(&self, name: &str, depth: usize) -> hex_ray::Result<Palette> {
+        let Some(theme) = self.themes.get(name) else {
+            return builtin_palette(name);
+        };
+        if depth > self.themes.len() {
+            return Err(Error::InvalidConfig(format!(
+                "The theme {:?} is based on itself",
+                name
+            )));
+        }
+
+        let mut palette = match theme.base.as_deref() {
+            // A theme can customize the built-in theme of the same name
+            Some(base) if base == name => builtin_palette(base)?,
+            Some(base) => self.resolve_palette(base, depth + 1)?,
+            None => Palette::default(),
+        };
+
+        let colors = [
+            (&mut palette.null, &theme.null),
+            (&mut palette.printable, &theme.printable),
+            (&mut palette.whitespace, &theme.whitespace),
+            (&mut palette.control, &theme.control),
+            (&mut palette.high, &theme.high),
+            (&mut palette.ff, &theme.ff),
+            (&mut palette.selected, &theme.selected),
+            (&mut palette.matched, &theme.matched),
+            (&mut palette.range, &theme.range),
+            (&mut palette.modified, &theme.modified),
+            (&mut palette.different, &theme.different),
+        ];
+        for (color, value) in colors {
+            match value {
+                Some(ColorValue::Index(n)) => *color = Color::Fixed(*n),
+                Some(ColorValue::Name(s)) => {
+                    *color = s.parse().map_err(|e| {
+                        Error::InvalidConfig(format!("{} in the theme {:?}", e, name))
+                    })?
+                }
+                None => {}
+            }
+        }
+        Ok(palette)
+    }
+}
+
+/// Returns the palette of the named built-in theme
+fn builtin_palette(name: &str) -> hex_ray::Result<Palette> {
+    Theme::from_str(name, true)
+        .map(Theme::palette)
+        .map_err(|_| Error::InvalidArgument(format!("Unknown theme {:?}", name)))
+}
+
+/// Set the default value of the argument. A flag that defaults to true is turned into an option
+/// with an optional value, so that it can be switched off again with e.g. `--plain=false`
+fn set_default(arg: clap::Arg, value: String) -> clap::Arg {
+    let arg = arg.default_value(value);
+    match arg.get_action() {
+        clap::ArgAction::SetTrue => arg
+            .action(clap::ArgAction::Set)
+            .num_args(0..=1)
+            .require_equals(true)
+            .default_missing_value("true"),
+        _ => arg,
+    }
+}
+
+/// Returns the id of the option of the command with the given long name (or alias)
+fn find_option(command: &clap::Command, name: &str) -> Option<clap::Id> {
+    command
+        .get_arguments()
+        .find(|arg| {
+            arg.get_long() == Some(name)
+                || arg
+                    .get_all_aliases()
+                    .is_some_and(|aliases| aliases.contains(&name))
+        })
+        .map(|arg| arg.get_id().clone())
+}
+
+/// The location of the config file: `$XDG_CONFIG_HOME/hex-ray/config.toml`, falling back to
+/// `~/.config/hex-ray/config.toml` (or `%APPDATA%\hex-ray\config.toml` on Windows)
+pub fn default_path() -> Option<PathBuf> {
+    let dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| match cfg!(windows) {
+            true => std::env::var_os("APPDATA").map(PathBuf::from),
+            false => std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")),
+        })?;
+    Some(dir.join(CONFIG_DIR).join(CONFIG_FILE))
+}
+
+/// Find the value of the `--config` option among the command line arguments. The config supplies
+/// the defaults of the other arguments, so it has to be read before they are parsed
+pub fn find_path_arg(args: impl IntoIterator<Item = OsString>) -> Option<PathBuf> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        if arg == "--config" {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.to_str().and_then(|a| a.strip_prefix("--config=")) {
+            return Some(PathBuf::from(path));
+        }
+    }
+    None
+}
+
+/// Keep the config around for the rest of the program
+pub fn set(config: Config) {
+    let _ = CONFIG.set(config);
+}
+
+/// The config that was loaded at startup (or the defaults if none was loaded)
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Args;
+    use clap::{CommandFactory, FromArgMatches};
+
+    const CONFIG: &str = r##"
+        [view]
+        size = 8
+        format = "#x"
+        plain = true
+
+        [output]
+        separator = ","
+
+        [themes.mine]
+        base = "gruvbox"
+        printable = "cyan"
+        null = 240
+        selected = "#ff0000"
+    "##;
+
+    fn parse(config: &Config, args: &[&str]) -> Args {
+        let matches = config
+            .apply(Args::command())
+            .unwrap()
+            .get_matches_from(args);
+        Args::from_arg_matches(&matches).unwrap()
+    }
+
+    #[test]
+    fn should_supply_default_values() {
+        let config = Config::parse(CONFIG).unwrap();
+
+        let Some(crate::cli::Command::Inspect(cmd)) = parse(&config, &["hex-ray", "inspect"]).cmd
+        else {
+            panic!("expected the inspect command");
+        };
+        assert_eq!((cmd.view.size, cmd.view.simple), (8, true));
+        assert!(matches!(cmd.view.format, hex_ray::Format::HexWithPrefix));
+
+        // The arguments on the command line take precedence
+        let Some(crate::cli::Command::View(cmd)) =
+            parse(&config, &["hex-ray", "view", "-s", "4"]).cmd
+        else {
+            panic!("expected the view command");
+        };
+        assert_eq!((cmd.size, cmd.group_size), (4, 4));
+
+        let Some(crate::cli::Command::Output(cmd)) = parse(&config, &["hex-ray", "output"]).cmd
+        else {
+            panic!("expected the output command");
+        };
+        assert_eq!(cmd.separator, ",");
+    }
+
+    #[test]
+    fn should_switch_off_flags_from_the_config() {
+        let config = Config::parse("[view]\nplain = true\nno-color = true").unwrap();
+        let view = |args: &[&str]| match parse(&config, args).cmd {
+            Some(crate::cli::Command::View(cmd)) => (cmd.simple, cmd.no_color),
+            _ => panic!("expected the view command"),
+        };
+        assert_eq!(view(&["hex-ray", "view"]), (true, true));
+        assert_eq!(view(&["hex-ray", "view", "--plain=false"]), (false, true));
+        assert_eq!(
+            view(&["hex-ray", "view", "-p", "--no-color=false"]),
+            (true, false)
+        );
+    }
+
+    #[test]
+    fn should_reject_unknown_options() {
+        let config = Config::parse("[view]\nsizes = 8").unwrap();
+        assert!(matches!(
+            config.apply(Args::command()),
+            Err(Error::InvalidConfig(_))
+        ));
+        assert!(Config::parse("[views]\nsize = 8").is_err());
+    }
+
+    #[test]
+    fn should_resolve_themes() {
+        let config = Config::parse(CONFIG).unwrap();
+        let palette = config.palette("mine").unwrap();
+        let gruvbox = Theme::Gruvbox.palette();
+        assert_eq!(palette.printable, Color::Cyan);
+        assert_eq!(palette.null, Color::Fixed(240));
+        assert_eq!(palette.selected, Color::Rgb(0xff, 0, 0));
+        assert_eq!(palette.ff, gruvbox.ff);
+        assert_eq!(
+            config.palette("solarized").unwrap(),
+            Theme::Solarized.palette()
+        );
+        assert!(config.palette("missing").is_err());
+
+        let config = Config::parse("[themes.a]\nbase = 'b'\n[themes.b]\nbase = 'a'").unwrap();
+        assert!(matches!(config.palette("a"), Err(Error::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn should_find_the_config_argument() {
+        let args = |args: &[&str]| find_path_arg(args.iter().map(OsString::from));
+        assert_eq!(
+            args(&["hex-ray", "--config", "a.toml", "view"]),
+            Some(PathBuf::from("a.toml"))
+        );
+        assert_eq!(
+            args(&["hex-ray", "view", "--config=b.toml"]),
+            Some(PathBuf::from("b.toml"))
+        );
+        assert_eq!(args(&["hex-ray", "view", "--", "--config"]), None);
+    }
+}
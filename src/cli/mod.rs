@@ -1,8 +1,9 @@
 // Library
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches, Parser};
 
 // Modules
 mod cmd;
+mod config;
 
 // ----------------------
 // COMMAND LINE ARGUMENTS
@@ -14,6 +15,22 @@ pub struct Args {
     /// Subcommand
     #[clap(subcommand)]
     pub cmd: Option<Command>,
+
+    /// Path to the config file (defaults to `$XDG_CONFIG_HOME/hex-ray/config.toml`)
+    #[arg(long, global = true)]
+    pub config: Option<std::path::PathBuf>,
+}
+
+impl Args {
+    /// Parse the command line arguments, with the default values supplied by the config file
+    pub fn load() -> hex_ray::Result<Self> {
+        let path = config::find_path_arg(std::env::args_os());
+        let config = config::Config::load(path.as_deref())?;
+        let matches = config.apply(Args::command())?.get_matches();
+        let args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+        config::set(config);
+        Ok(args)
+    }
 }
 
 #[derive(clap::Subcommand, Clone)]
@@ -23,5 +40,28 @@ pub enum Command {
     /// Outputs only the values
     Output(cmd::Output),
     /// View using an interactive Terminal User Interface
-    Inspect(cmd::View),
+    Inspect(cmd::Inspect),
+    /// Rebuilds the binary data from a hex-dump
+    Revert(cmd::Revert),
+    /// Patches the bytes from a hex-dump into an existing file
+    Patch(cmd::Revert),
+    /// Compares two files side by side
+    Diff(cmd::Diff),
+    /// Extracts the runs of printable text
+    Strings(cmd::Strings),
+    /// Reports the byte histogram and entropy
+    Stats(cmd::Stats),
+}
+
+// ----------
+// VALIDATION
+// ----------
+
+/// Parse a count (e.g. a row or group size) that must be at least 1
+pub fn parse_count(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(0) => Err(String::from("must be at least 1")),
+        Ok(n) => Ok(n),
+        Err(e) => Err(e.to_string()),
+    }
 }
@@ -0,0 +1,560 @@
+// Library
+use crate::error::{Error, Result};
+use crate::utils::{
+    ansi::Color,
+    encoding::{Encoding, Glyph, Substitution},
+    format::{Format, Radix},
+    helpers,
+    theme::{ByteClass, Palette},
+};
+use std::io::{Read, Write};
+use std::ops::Range;
+
+// ------
+// DUMPER
+// ------
+
+/// Writes out the hex-dump of the bytes from a reader, as a table of rows with an offset column,
+/// the formatted values and their ASCII representation (the same table that `hex-ray view` prints).
+///
+/// ```
+/// use hex_ray::{Dumper, Format};
+///
+/// let mut output = Vec::new();
+/// Dumper::new()
+///     .size(8)
+///     .format(Format::UpperHex)
+///     .plain(true)
+///     .dump(&b"hex-ray!"[..], 0, &mut output)
+///     .unwrap();
+/// assert_eq!(
+///     String::from_utf8(output).unwrap(),
+///     "00000000:  68 65 78 2D  72 61 79 21   | hex- ray!\n"
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct Dumper {
+    size: usize,                // The number of bytes in each row
+    group_size: usize,          // The number of bytes in each group of a row
+    format: Format,             // The format of the values
+    radix: Radix,               // The radix of the offsets
+    offset_width: usize,        // The minimum number of digits in the offset column
+    limit: Option<usize>,       // The maximum number of bytes to read
+    color: bool,                // Whether to color the output with ANSI escape codes
+    palette: Palette,           // The colors of each class of bytes
+    encoding: Encoding,         // The character encoding of the text column
+    substitution: Substitution, // The glyphs that stand in for the characters that cannot be shown
+    plain: bool,                // Whether to leave out the borders of the table
+    squeeze: bool,              // Whether to collapse runs of identical rows into a `*` line
+}
+
+impl Default for Dumper {
+    fn default() -> Self {
+        Self {
+            size: 16,
+            group_size: 4,
+            format: Format::default(),
+            radix: Radix::default(),
+            offset_width: Radix::default().width(0),
+            limit: None,
+            color: false,
+            palette: Palette::default(),
+            encoding: Encoding::default(),
+            substitution: Substitution::default(),
+            plain: false,
+            squeeze: false,
+        }
+    }
+}
+
+impl Dumper {
+    /// Instantiate a new dumper with the default configuration; 16 hex values per row in groups of 4
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of bytes in each row
+    pub fn size(mut self, size: usize) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Set the number of bytes in each group of a row
+    pub fn group_size(mut self, group_size: usize) -> Self {
+        self.group_size = group_size;
+        self
+    }
+
+    /// Set the format of the values
+    pub fn format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Set the radix of the offset column
+    pub fn radix(mut self, radix: Radix) -> Self {
+        self.radix = radix;
+        self
+    }
+
+    /// Set the minimum number of digits in the offset column. Use [`Radix::width()`] to fit
+    /// the largest offset that will be written
+    pub fn offset_width(mut self, offset_width: usize) -> Self {
+        self.offset_width = offset_width;
+        self
+    }
+
+    /// Set the maximum number of bytes to read (or `None` to read to the end)
+    pub fn limit(mut self, limit: Option<usize>) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Set whether to color the output with ANSI escape codes
+    pub fn color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Set the colors of each class of bytes (see [`crate::Theme`] for the built-in palettes)
+    pub fn palette(mut self, palette: Palette) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Set the character encoding of the text column
+    pub fn encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Set the glyphs that stand in for the control characters (and the bytes that cannot be
+    /// shown) in the text column, rather than writing them to the terminal raw
+    pub fn substitution(mut self, substitution: Substitution) -> Self {
+        self.substitution = substitution;
+        self
+    }
+
+    /// Set whether to write plain rows without the borders of the table
+    pub fn plain(mut self, plain: bool) -> Self {
+        self.plain = plain;
+        self
+    }
+
+    /// Set whether to collapse consecutive rows that repeat the row before them into a single `*`
+    /// line (like `hexdump` does). When the dump ends on such a run, plain rows end with a line
+    /// holding only the offset of the end of the data, so that the dump can be reverted
+    pub fn squeeze(mut self, squeeze: bool) -> Self {
+        self.squeeze = squeeze;
+        self
+    }
+
+    /// Write out the table for the bytes from the reader, whose first byte is at the `offset`.
+    ///
+    /// Returns the number of bytes read.
+    pub fn dump<R, W>(&self, reader: R, offset: usize, writer: &mut W) -> Result<usize>
+    where
+        R: Read,
+        W: Write,
+    {
+        self.validate()?;
+
+        // Never read beyond the limit, even to complete the characters at the end of the last row
+        let mut reader = reader.take(self.limit.map_or(u64::MAX, |limit| limit as u64));
+
+        // The bytes of the row, along with the bytes around it that the text column decodes
+        let mut window = Vec::new();
+        // The position of the row in the window
+        let mut row_start = 0;
+
+        // The total number of bytes read already
+        let mut total_bytes_read = 0;
+
+        // The last row that was written out, and whether the rows after it are being squeezed
+        let mut previous: Option<Vec<u8>> = None;
+        let mut squeezing = false;
+
+        if !self.plain {
+            writeln!(writer, "{}", self.format_border("┌─", "┬─", "─┬─", "─┐"))?;
+        }
+
+        loop {
+            // Read the rest of the row, and the bytes after it
+            let window_end = self.encoding.window(row_start..row_start + self.size).end;
+            if window.len() < window_end {
+                let len = window.len();
+                window.resize(window_end, 0);
+                let bytes_read = helpers::read_chunk(&mut reader, &mut window[len..])?;
+                window.truncate(len + bytes_read);
+            }
+
+            let row = row_start..std::cmp::min(row_start + self.size, window.len());
+            if row.is_empty() {
+                break;
+            }
+
+            let bytes = &window[row.clone()];
+            if self.squeeze && previous.as_deref() == Some(bytes) {
+                if !squeezing {
+                    writeln!(writer, "{}", self.format_squeezed_row())?;
+                    squeezing = true;
+                }
+            } else {
+                self.write_row_in(writer, &window, row.clone(), offset + total_bytes_read)?;
+                squeezing = false;
+                if self.squeeze {
+                    previous = Some(bytes.to_vec());
+                }
+            }
+            total_bytes_read += row.len();
+
+            // Keep only the bytes before the next row that its text column decodes
+            let next_start = self.encoding.window(row.end..row.end).start;
+            window.drain(..next_start);
+            row_start = row.end - next_start;
+        }
+
+        if squeezing && self.plain {
+            writeln!(writer, "{}:", self.format_offset(offset + total_bytes_read))?;
+        }
+
+        if !self.plain {
+            writeln!(writer, "{}", self.format_border("└─", "┴─", "─┴─", "─┘"))?;
+        }
+        writer.flush()?;
+
+        Ok(total_bytes_read)
+    }
+
+    /// Write out just the formatted values of the bytes from the reader, each followed by the separator.
+    ///
+    /// Returns the number of bytes read.
+    pub fn dump_values<R, W>(&self, mut reader: R, separator: &str, writer: &mut W) -> Result<usize>
+    where
+        R: Read,
+        W: Write,
+    {
+        // Buffer to store the data (holds a whole number of values)
+        let size = 16 * self.format.bytes();
+        let mut buffer = vec![0; size];
+
+        let mut total_bytes_read = 0;
+        let mut bytes_remaining = self.limit.unwrap_or(usize::MAX);
+
+        while bytes_remaining > 0 {
+            let bytes_to_read = std::cmp::min(bytes_remaining, size);
+
+            let bytes_read = helpers::read_chunk(&mut reader, &mut buffer[0..bytes_to_read])?;
+            if bytes_read == 0 {
+                break;
+            }
+
+            for bytes in buffer[..bytes_read].chunks(self.format.bytes()) {
+                write!(writer, "{}{}", self.format.format_bytes(bytes), separator)?;
+            }
+
+            total_bytes_read += bytes_read;
+            bytes_remaining -= bytes_read;
+        }
+        writer.flush()?;
+
+        Ok(total_bytes_read)
+    }
+
+    /// Ensure that the configuration can lay out a table
+    pub fn validate(&self) -> Result<()> {
+        if self.size == 0 {
+            return Err(Error::InvalidArgument(String::from(
+                "The size of each row must be at least 1",
+            )));
+        }
+        if self.group_size == 0 {
+            return Err(Error::InvalidArgument(String::from(
+                "The size of each group must be at least 1",
+            )));
+        }
+        Ok(())
+    }
+
+    /// Write out a single row of the table for the bytes (up to a row's worth) at the `offset`
+    pub fn write_row<W>(&self, writer: &mut W, bytes: &[u8], offset: usize) -> std::io::Result<()>
+    where
+        W: Write,
+    {
+        self.write_row_in(writer, bytes, 0..bytes.len(), offset)
+    }
+
+    /// Write out a single row of the table for the `row` of bytes in the `window`, whose bytes
+    /// around the row complete the characters that cross into it (see [`Encoding::window()`])
+    pub fn write_row_in<W>(
+        &self,
+        writer: &mut W,
+        window: &[u8],
+        row: Range<usize>,
+        offset: usize,
+    ) -> std::io::Result<()>
+    where
+        W: Write,
+    {
+        let offset = self.format_offset(offset);
+        let values = self.format_values(&window[row.clone()]);
+        let text = self.format_text(&window[row.clone()], &self.encoding.decode_row(window, row));
+        writeln!(writer, "{}", self.format_row(&offset, &values, &text))
+    }
+
+    /// Returns the `*` line that stands in for a run of squeezed rows
+    pub fn format_squeezed_row(&self) -> String {
+        if self.plain {
+            return String::from("*");
+        }
+
+        let offset = format!(" {:>width$}", "*", width = self.offset_width);
+        self.format_row(&offset, &self.format_values(&[]), &self.format_ascii(&[]))
+    }
+
+    /// Returns a horizontal border of the table, joined by the given corner and junction pieces
+    pub fn format_border(&self, left: &str, first: &str, second: &str, right: &str) -> String {
+        let mut line = String::from(left);
+        line.push_str(&"─".repeat(self.offset_width + 2));
+        line.push_str(first);
+
+        for i in 0..self.values_per_row() {
+            if self.is_group_start(i) {
+                line.push('─');
+            }
+            line.push_str(&"─".repeat(self.format.size() + 1));
+        }
+
+        line.push_str(second);
+
+        for i in 0..self.size {
+            if i > 0 && i % self.group_size == 0 {
+                line.push('─');
+            }
+            line.push_str(&"─".repeat(self.substitution.width()));
+        }
+
+        line.push_str(right);
+        line
+    }
+
+    /// Lays out the formatted columns of a row in the table
+    pub fn format_row(&self, offset: &str, values: &str, ascii: &str) -> String {
+        if self.plain {
+            format!("{}:  {}  | {}", offset, values, ascii)
+        } else {
+            format!("│ {} │ {} │ {} │", offset, values, ascii)
+        }
+    }
+
+    /// Format the offset column
+    pub fn format_offset(&self, offset: usize) -> String {
+        let res = self.radix.format(offset as u64);
+        let padding = self.offset_width.saturating_sub(res.len());
+        if self.plain {
+            return format!("{}{}", "0".repeat(padding), res);
+        }
+
+        format!(
+            " {}{}",
+            self.paint(&"·".repeat(padding), Color::Black),
+            self.paint(&res, Color::White)
+        )
+    }
+
+    /// Returns a blank offset column, for rows that have no offset
+    pub fn format_blank_offset(&self) -> String {
+        " ".repeat(if self.plain {
+            self.offset_width
+        } else {
+            self.offset_width + 1
+        })
+    }
+
+    /// Format the values column, padded out to a full row
+    pub fn format_values(&self, bytes: &[u8]) -> String {
+        let mut s = String::new();
+        for (j, chunk) in bytes.chunks(self.format.bytes()).enumerate() {
+            // Group values by applying spacing
+            if self.is_group_start(j) {
+                s.push(' ');
+            }
+            let value = self.format.format_bytes(chunk); // Format each value (e.g. as a 2-wide hexadecimal value)
+            let color = self.palette.color(ByteClass::of_bytes(chunk));
+            s.push_str(&self.paint(&value, color));
+            s.push(' ');
+        }
+
+        // Print spacing if the row is less than size bytes
+        for k in bytes.len().div_ceil(self.format.bytes())..self.values_per_row() {
+            // Group values by applying spacing
+            if self.is_group_start(k) {
+                s.push(' ');
+            }
+
+            s.push_str(&" ".repeat(self.format.size() + 1)); // Each missing value is represented by spaces (one for each digit and one separator)
+        }
+
+        s
+    }
+
+    /// Format the text column of the bytes on their own, padded out to a full row
+    pub fn format_ascii(&self, bytes: &[u8]) -> String {
+        self.format_text(bytes, &self.encoding.decode(bytes))
+    }
+
+    /// Format the text column from the glyph of each byte, padded out to a full row
+    pub fn format_text(&self, bytes: &[u8], glyphs: &[Glyph]) -> String {
+        let mut s = String::new();
+
+        for k in 0..self.size {
+            // Group characters by applying spacing
+            if k > 0 && k % self.group_size == 0 {
+                s.push(' ');
+            }
+
+            // If there is a byte at this position, print its character (or its substitute if it is not printable)...
+            match bytes.get(k).zip(glyphs.get(k)) {
+                Some((byte, glyph)) => {
+                    let color = self.palette.color(ByteClass::of(*byte));
+                    s.push_str(&self.paint(&self.substitution.render(*glyph), color));
+                }
+                None => s.push_str(&" ".repeat(self.substitution.width())), // Else if the row has ended, just print an empty space
+            }
+        }
+
+        s
+    }
+
+    /// The number of formatted values in each row
+    pub fn values_per_row(&self) -> usize {
+        self.size.div_ceil(self.format.bytes())
+    }
+
+    /// Returns true if the value at the given index in a row begins a new group
+    pub fn is_group_start(&self, index: usize) -> bool {
+        let byte_index = index * self.format.bytes();
+        byte_index > 0 && byte_index.is_multiple_of(self.group_size)
+    }
+
+    /// Color the text, if colors are enabled
+    fn paint(&self, text: &str, color: Color) -> String {
+        match self.color {
+            true => color.paint(text),
+            false => text.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dump(dumper: &Dumper, data: &[u8], offset: usize) -> String {
+        let mut output = Vec::new();
+        dumper.dump(data, offset, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn should_dump_a_table() {
+        let dumper = Dumper::new().size(4).group_size(2);
+        assert_eq!(
+            dump(&dumper, b"abcdef", 0x10),
+            "┌───────────┬───────────────┬───────┐\n\
+             │  ······10 │ 61 62  63 64  │ ab cd │\n\
+             │  ······14 │ 65 66         │ ef    │\n\
+             └───────────┴───────────────┴───────┘\n"
+        );
+    }
+
+    #[test]
+    fn should_respect_the_limit_and_radix() {
+        let dumper = Dumper::new()
+            .size(4)
+            .radix(Radix::Decimal)
+            .limit(Some(6))
+            .plain(true);
+        assert_eq!(
+            dump(&dumper, b"abcdefgh", 100),
+            "00000100:  61 62 63 64   | abcd\n00000104:  65 66         | ef  \n"
+        );
+
+        let mut output = Vec::new();
+        let n = dumper
+            .dump_values(&b"abcdefgh"[..], ",", &mut output)
+            .unwrap();
+        assert_eq!((n, output.as_slice()), (6, &b"61,62,63,64,65,66,"[..]));
+    }
+
+    #[test]
+    fn should_squeeze_repeated_rows() {
+        let dumper = Dumper::new().size(2).squeeze(true);
+        assert_eq!(
+            dump(&dumper, b"abababcdcde", 0),
+            "┌───────────┬────────┬────┐\n\
+             │  ·······0 │ 61 62  │ ab │\n\
+             │         * │        │    │\n\
+             │  ·······6 │ 63 64  │ cd │\n\
+             │         * │        │    │\n\
+             │  ·······a │ 65     │ e  │\n\
+             └───────────┴────────┴────┘\n"
+        );
+
+        let dumper = dumper.plain(true);
+        assert_eq!(
+            dump(&dumper, b"abababab", 0),
+            "00000000:  61 62   | ab\n*\n00000008:\n"
+        );
+    }
+
+    #[test]
+    fn should_decode_the_text_column() {
+        let dumper = Dumper::new().size(4).plain(true).encoding(Encoding::Utf8);
+        assert_eq!(
+            dump(&dumper, "abcé中!".as_bytes(), 0),
+            "00000000:  61 62 63 c3   | abcé\n00000004:  a9 e4 b8 ad   |  中 \n00000008:  21            | !   \n"
+        );
+
+        let dumper = Dumper::new().size(4).plain(true).encoding(Encoding::Cp037);
+        assert_eq!(
+            dump(&dumper, &[0xc8, 0x89, 0x5a, 0x25], 0),
+            "00000000:  c8 89 5a 25   | Hi!·\n"
+        );
+    }
+
+    #[test]
+    fn should_substitute_control_characters() {
+        let dumper = Dumper::new().size(4).group_size(2);
+        assert_eq!(
+            dump(&dumper.clone(), b"a \x1b\xff", 0),
+            "┌───────────┬───────────────┬───────┐\n\
+             │  ·······0 │ 61 20  1b ff  │ a  ·· │\n\
+             └───────────┴───────────────┴───────┘\n"
+        );
+        assert_eq!(
+            dump(
+                &dumper.clone().substitution(Substitution::Pictures),
+                b"a \x1b\xff",
+                0
+            ),
+            "┌───────────┬───────────────┬───────┐\n\
+             │  ·······0 │ 61 20  1b ff  │ a  ␛· │\n\
+             └───────────┴───────────────┴───────┘\n"
+        );
+        assert_eq!(
+            dump(&dumper.substitution(Substitution::Caret), b"a \x1b\xff", 0),
+            "┌───────────┬───────────────┬───────────┐\n\
+             │  ·······0 │ 61 20  1b ff  │ a    ^[·  │\n\
+             └───────────┴───────────────┴───────────┘\n"
+        );
+    }
+
+    #[test]
+    fn should_reject_empty_groups() {
+        let result = Dumper::new()
+            .group_size(0)
+            .dump(&b"abc"[..], 0, &mut Vec::new());
+        assert!(matches!(result, Err(Error::InvalidArgument(_))));
+    }
+}
@@ -0,0 +1,82 @@
+// -----
+// ERROR
+// -----
+
+/// The errors that can occur while reading, formatting and writing out the data
+#[derive(Debug)]
+pub enum Error {
+    /// Reading from the input or writing to the output failed
+    Io(std::io::Error),
+    /// The offset lies beyond either end of the input
+    OffsetOutOfRange { offset: i64, size: u64 },
+    /// The display format is not one of the supported formats
+    InvalidFormat(String),
+    /// An argument is invalid, either on its own or in combination with the others
+    InvalidArgument(String),
+    /// The input could not be parsed (e.g. a malformed hex-dump)
+    InvalidInput(String),
+    /// The terminal could not be set up or drawn to
+    Terminal(std::io::Error),
+    /// The config file could not be read or is invalid
+    InvalidConfig(String),
+}
+
+/// A result with the crate's [`Error`] type
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// The code to exit the process with; each kind of error has its own.
+    ///
+    /// Invalid arguments share the exit code of the usage errors reported by clap.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Io(_) => 1,
+            Error::InvalidArgument(_) => 2,
+            Error::InvalidFormat(_) => 3,
+            Error::OffsetOutOfRange { .. } => 4,
+            Error::InvalidInput(_) => 5,
+            Error::Terminal(_) => 6,
+            Error::InvalidConfig(_) => 7,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::OffsetOutOfRange { offset, size } => write!(
+                f,
+                "The offset {} is out of range for the {} bytes of input",
+                offset, size
+            ),
+            Error::InvalidFormat(format) => write!(
+                f,
+                "Invalid format {:?}; expected one of hex (x), HEX (X), binary (b), octal (o), \
+                 decimal (d), u16, i16, u32, i32, u64, i64, u128, i128, f16, f32 or f64 \
+                 (optionally followed by `le` or `be`, and prefixed with `#`)",
+                format
+            ),
+            Error::InvalidArgument(message) | Error::InvalidInput(message) => {
+                write!(f, "{}", message)
+            }
+            Error::Terminal(e) => write!(f, "Failed to draw to the terminal: {}", e),
+            Error::InvalidConfig(message) => write!(f, "Invalid config: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) | Error::Terminal(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
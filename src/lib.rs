@@ -0,0 +1,17 @@
+//! `hex-ray` is a hexdump utility. Besides the command-line tool, this library exposes the
+//! [`Dumper`] that lays out the hex-dump tables, the [`Format`] of the values, and the helpers
+//! to read from files (memory-mapped where possible) or STDIN.
+
+// Modules
+pub mod dump;
+pub mod error;
+pub mod strings;
+pub mod utils;
+
+// Re-exports
+pub use dump::Dumper;
+pub use error::{Error, Result};
+pub use strings::{Scanner, StringEncoding};
+pub use utils::encoding::Encoding;
+pub use utils::format::{Endianness, Format, Radix};
+pub use utils::theme::{ByteClass, Palette, Theme};
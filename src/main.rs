@@ -1,27 +1,31 @@
-// Traits
-use clap::Parser;
-
 // Modules
 mod cli;
-mod utils;
 
 fn main() {
-    let args = cli::Args::parse();
-    match run(args) {
+    match cli::Args::load().and_then(run) {
         Ok(_) => std::process::exit(0),
+        // The reader of the output went away (e.g. `hex-ray view | head`), so just stop
+        Err(hex_ray::Error::Io(e)) if e.kind() == std::io::ErrorKind::BrokenPipe => {
+            std::process::exit(0)
+        }
         Err(e) => {
             eprintln!("Error: {}", e);
-            std::process::exit(1);
+            std::process::exit(e.exit_code());
         }
     }
 }
 
-fn run(args: cli::Args) -> Result<(), Box<dyn std::error::Error>> {
-    let ret = match args.cmd {
+fn run(args: cli::Args) -> hex_ray::Result<()> {
+    match args.cmd {
         Some(cli::Command::View(cmd)) => cmd.execute()?,
         Some(cli::Command::Output(cmd)) => cmd.execute()?,
-        Some(cli::Command::Inspect(cmd)) => cmd.execute_interactively()?,
+        Some(cli::Command::Inspect(cmd)) => cmd.execute()?,
+        Some(cli::Command::Revert(cmd)) => cmd.execute()?,
+        Some(cli::Command::Patch(cmd)) => cmd.patch()?,
+        Some(cli::Command::Diff(cmd)) => cmd.execute()?,
+        Some(cli::Command::Strings(cmd)) => cmd.execute()?,
+        Some(cli::Command::Stats(cmd)) => cmd.execute()?,
         _ => {}
     };
-    Ok(ret)
+    Ok(())
 }
@@ -0,0 +1,384 @@
+// Library
+use crate::error::Result;
+use crate::utils::helpers;
+use std::io::Read;
+
+/// The most text that is held for a run. Longer runs are split into pieces of this length
+pub const MAX_TEXT_LEN: usize = 4096;
+
+// -------
+// STRINGS
+// -------
+
+/// The encodings in which runs of text are looked for
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StringEncoding {
+    /// Printable 7-bit ASCII
+    Ascii,
+    /// Printable UTF-8, which includes the printable ASCII
+    #[value(alias = "utf-8")]
+    Utf8,
+    /// UTF-16 (little-endian) characters from the Latin-1 range
+    #[value(alias = "utf-16le", alias = "utf16")]
+    Utf16le,
+    /// UTF-16 (big-endian) characters from the Latin-1 range
+    #[value(alias = "utf-16be")]
+    Utf16be,
+}
+
+impl StringEncoding {
+    /// The name of the encoding, as passed on the command line
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ascii => "ascii",
+            Self::Utf8 => "utf8",
+            Self::Utf16le => "utf16le",
+            Self::Utf16be => "utf16be",
+        }
+    }
+}
+
+/// A run of text found in the data
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Found {
+    pub offset: usize,            // The position of the first byte of the run
+    pub len: usize,               // The number of bytes in the run
+    pub encoding: StringEncoding, // The encoding the run was decoded with
+    pub text: String,             // The decoded text
+}
+
+/// Returns true if the character can be part of a run of text (tabs are, other controls are not)
+fn is_text(c: char) -> bool {
+    c == '\t' || !c.is_control()
+}
+
+/// The run of text being decoded in one of the encodings
+#[derive(Debug)]
+struct Track {
+    encoding: StringEncoding, // The encoding of the run
+    parity: usize,            // The alignment of the UTF-16 code units (0 or 1)
+    start: usize,             // The position of the first byte of the run
+    text: String,             // The text decoded so far
+    chars: usize,             // The number of characters in the text
+    continued: bool,          // Whether the run continues a piece of a longer run
+    pending: Vec<u8>,         // The bytes of a character that is not complete yet
+    pending_start: usize,     // The position of the first pending byte
+}
+
+impl Track {
+    fn new(encoding: StringEncoding, parity: usize) -> Self {
+        Self {
+            encoding,
+            parity,
+            start: 0,
+            text: String::new(),
+            chars: 0,
+            continued: false,
+            pending: Vec::new(),
+            pending_start: 0,
+        }
+    }
+
+    /// The position of the first byte that may still become part of a run
+    fn open_start(&self) -> Option<usize> {
+        if self.chars > 0 {
+            Some(self.start)
+        } else if !self.pending.is_empty() {
+            Some(self.pending_start)
+        } else {
+            None
+        }
+    }
+
+    /// Append a character, which begins at the given position, to the run. A run that holds
+    /// too much text already is split before the character
+    fn push(&mut self, c: char, position: usize, found: &mut Vec<Found>) {
+        if self.text.len() >= MAX_TEXT_LEN {
+            self.end(position, 0, found);
+            self.continued = true;
+        }
+        if self.chars == 0 {
+            self.start = position;
+        }
+        self.text.push(c);
+        self.chars += 1;
+    }
+
+    /// End the run before the given position, keeping it if it is long enough
+    fn end(&mut self, position: usize, min_len: usize, found: &mut Vec<Found>) {
+        // The pieces of a longer run are kept, however short the last one is
+        if self.chars >= min_len || (self.continued && self.chars > 0) {
+            found.push(Found {
+                offset: self.start,
+                len: position - self.start,
+                encoding: self.encoding,
+                text: std::mem::take(&mut self.text),
+            });
+        }
+        self.text.clear();
+        self.chars = 0;
+        self.continued = false;
+    }
+
+    /// Decode the byte at the given position
+    fn feed(&mut self, byte: u8, position: usize, min_len: usize, found: &mut Vec<Found>) {
+        match self.encoding {
+            StringEncoding::Ascii => {
+                if helpers::is_printable_ascii_character(&byte) || byte == b'\t' {
+                    self.push(byte as char, position, found);
+                } else {
+                    self.end(position, min_len, found);
+                }
+            }
+            StringEncoding::Utf8 => self.feed_utf8(byte, position, min_len, found),
+            StringEncoding::Utf16le | StringEncoding::Utf16be => {
+                if position % 2 == self.parity {
+                    self.pending = vec![byte];
+                    self.pending_start = position;
+                    return;
+                }
+                let Some(&first) = self.pending.first() else {
+                    return;
+                };
+                self.pending.clear();
+
+                // Only the characters from the Latin-1 range are taken, as almost any pair of
+                // bytes is a printable character otherwise
+                let (low, high) = match self.encoding {
+                    StringEncoding::Utf16le => (first, byte),
+                    _ => (byte, first),
+                };
+                let c = char::from(low);
+                if high == 0 && is_text(c) {
+                    self.push(c, position - 1, found);
+                } else {
+                    self.end(position - 1, min_len, found);
+                }
+            }
+        }
+    }
+
+    /// Decode the byte at the given position as a part of a UTF-8 character
+    fn feed_utf8(&mut self, byte: u8, position: usize, min_len: usize, found: &mut Vec<Found>) {
+        if !self.pending.is_empty() {
+            if byte & 0xC0 != 0x80 {
+                // The character was cut short, so the byte may begin the next one
+                self.pending.clear();
+                self.end(self.pending_start, min_len, found);
+                return self.feed_utf8(byte, position, min_len, found);
+            }
+
+            self.pending.push(byte);
+            let expected = match self.pending[0] {
+                0xC0..=0xDF => 2,
+                0xE0..=0xEF => 3,
+                _ => 4,
+            };
+            if self.pending.len() < expected {
+                return;
+            }
+
+            let pending = std::mem::take(&mut self.pending);
+            match std::str::from_utf8(&pending)
+                .ok()
+                .and_then(|s| s.chars().next())
+            {
+                Some(c) if is_text(c) => self.push(c, self.pending_start, found),
+                _ => self.end(self.pending_start, min_len, found),
+            }
+            return;
+        }
+
+        match byte {
+            0x00..=0x7F if is_text(byte as char) => self.push(byte as char, position, found),
+            0xC2..=0xF4 => {
+                self.pending.push(byte);
+                self.pending_start = position;
+            }
+            _ => self.end(position, min_len, found),
+        }
+    }
+
+    /// End the run at the end of the data
+    fn finish(&mut self, position: usize, min_len: usize, found: &mut Vec<Found>) {
+        let end = match self.pending.is_empty() {
+            true => position,
+            false => self.pending_start,
+        };
+        self.pending.clear();
+        self.end(end, min_len, found);
+    }
+}
+
+/// Extracts the runs of printable text from data that is fed through it in chunks.
+///
+/// A run is kept if it has at least the minimum number of characters. The runs of each
+/// encoding are looked for independently, so the same bytes can be part of runs of several
+/// encodings (e.g. every ASCII run is a UTF-8 run too). Runs with more than [`MAX_TEXT_LEN`]
+/// bytes of text are split into pieces, so that the memory held stays bounded.
+#[derive(Debug)]
+pub struct Scanner {
+    min_len: usize,     // The minimum number of characters in a run
+    tracks: Vec<Track>, // The run being decoded in each encoding (and UTF-16 alignment)
+    position: usize,    // The position of the next byte
+    found: Vec<Found>,  // The runs that ended, but may begin after a run that is still open
+}
+
+impl Scanner {
+    /// Create a scanner for the given encodings, where the first byte is at the `offset`
+    pub fn new(encodings: &[StringEncoding], min_len: usize, offset: usize) -> Self {
+        let mut tracks = Vec::new();
+        for &encoding in encodings {
+            match encoding {
+                StringEncoding::Ascii | StringEncoding::Utf8 => {
+                    tracks.push(Track::new(encoding, 0))
+                }
+                // UTF-16 text can begin at either an even or an odd position
+                StringEncoding::Utf16le | StringEncoding::Utf16be => {
+                    tracks.push(Track::new(encoding, 0));
+                    tracks.push(Track::new(encoding, 1));
+                }
+            }
+        }
+        Self {
+            min_len: min_len.max(1),
+            tracks,
+            position: offset,
+            found: Vec::new(),
+        }
+    }
+
+    /// Feed the next chunk of the data, and return the runs that are complete, ordered by
+    /// their offset. Runs are only returned once no run that begins before them is still open
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<Found> {
+        for &byte in chunk {
+            for track in &mut self.tracks {
+                track.feed(byte, self.position, self.min_len, &mut self.found);
+            }
+            self.position += 1;
+        }
+
+        let open = self.tracks.iter().filter_map(Track::open_start).min();
+        self.found.sort_by_key(|found| found.offset);
+        let ready = match open {
+            Some(open) => self.found.partition_point(|found| found.offset < open),
+            None => self.found.len(),
+        };
+        self.found.drain(..ready).collect()
+    }
+
+    /// End the runs at the end of the data, and return the rest of the runs
+    pub fn finish(mut self) -> Vec<Found> {
+        for track in &mut self.tracks {
+            track.finish(self.position, self.min_len, &mut self.found);
+        }
+        self.found.sort_by_key(|found| found.offset);
+        self.found
+    }
+
+    /// Read the reader to the end (or up to the `limit`), and call `f` with each run in order.
+    /// Returns the number of bytes read
+    pub fn scan<R, F>(mut self, reader: R, limit: Option<usize>, mut f: F) -> Result<usize>
+    where
+        R: Read,
+        F: FnMut(Found) -> Result<()>,
+    {
+        let mut reader = reader.take(limit.map_or(u64::MAX, |limit| limit as u64));
+        let mut buffer = vec![0; 64 * 1024];
+        let mut total_bytes_read = 0;
+        loop {
+            let bytes_read = helpers::read_chunk(&mut reader, &mut buffer)?;
+            if bytes_read == 0 {
+                break;
+            }
+            total_bytes_read += bytes_read;
+            self.feed(&buffer[..bytes_read])
+                .into_iter()
+                .try_for_each(&mut f)?;
+        }
+        self.finish().into_iter().try_for_each(f)?;
+        Ok(total_bytes_read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extract(data: &[u8], encodings: &[StringEncoding], min_len: usize) -> Vec<Found> {
+        let mut found = Vec::new();
+        Scanner::new(encodings, min_len, 100)
+            .scan(data, None, |f| {
+                found.push(f);
+                Ok(())
+            })
+            .unwrap();
+        found
+    }
+
+    #[test]
+    fn should_extract_runs_of_text() {
+        let data = b"\x00\x01hello\xffabc\x00caf\xc3\xa9!\xc3\x00";
+        let found = extract(data, &[StringEncoding::Ascii], 4);
+        let texts: Vec<_> = found
+            .iter()
+            .map(|f| (f.offset, f.len, f.text.as_str()))
+            .collect();
+        assert_eq!(texts, vec![(102, 5, "hello")]);
+
+        let found = extract(data, &[StringEncoding::Utf8], 4);
+        let texts: Vec<_> = found
+            .iter()
+            .map(|f| (f.offset, f.len, f.text.as_str()))
+            .collect();
+        assert_eq!(texts, vec![(102, 5, "hello"), (112, 6, "café!")]);
+    }
+
+    #[test]
+    fn should_extract_utf16_runs_at_either_alignment() {
+        let data = b"\x01W\x00i\x00n\x00\xe9\x00\x00\x00\x00a\x00b\x00c\x00d";
+        let found = extract(data, &[StringEncoding::Utf16le, StringEncoding::Utf16be], 4);
+        let texts: Vec<_> = found
+            .iter()
+            .map(|f| (f.offset, f.len, f.encoding, f.text.as_str()))
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                (101, 8, StringEncoding::Utf16le, "Winé"),
+                (111, 8, StringEncoding::Utf16be, "abcd"),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_extract_the_same_runs_from_any_chunks() {
+        let data = b"one\x00two three\x00\xe2\x82\xacuro \x00a\x00s\x00c\x00i\x00i\x00";
+        let encodings = [StringEncoding::Utf8, StringEncoding::Utf16le];
+        let expected = extract(data, &encodings, 3);
+        for size in 1..8 {
+            let mut scanner = Scanner::new(&encodings, 3, 100);
+            let mut found = Vec::new();
+            for chunk in data.chunks(size) {
+                found.extend(scanner.feed(chunk));
+            }
+            found.extend(scanner.finish());
+            assert_eq!(found, expected);
+        }
+    }
+
+    #[test]
+    fn should_split_long_runs_into_pieces() {
+        let data = [vec![b'a'; MAX_TEXT_LEN * 2 + 2], vec![0]].concat();
+        let found = extract(&data, &[StringEncoding::Ascii], 4);
+        let pieces: Vec<_> = found.iter().map(|f| (f.offset, f.len)).collect();
+        assert_eq!(
+            pieces,
+            vec![
+                (100, MAX_TEXT_LEN),
+                (100 + MAX_TEXT_LEN, MAX_TEXT_LEN),
+                (100 + MAX_TEXT_LEN * 2, 2),
+            ]
+        );
+    }
+}
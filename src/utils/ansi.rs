@@ -5,54 +5,139 @@
 /// The string representing the NO_COLOR environment variable
 const ENV_NO_COLOR: &str = "NO_COLOR";
 
+/// The string representing the COLORTERM environment variable, which advertises truecolor support
+const ENV_COLORTERM: &str = "COLORTERM";
+
 /// Returns a boolean to indicate whether ANSI Colors are enabled
 pub fn is_color_enabled() -> bool {
     !std::env::var(ENV_NO_COLOR).is_ok_and(|e| e.to_lowercase() == "true")
 }
+
+/// Returns a boolean to indicate whether the terminal supports 24-bit colors
+pub fn is_truecolor_enabled() -> bool {
+    std::env::var(ENV_COLORTERM).is_ok_and(|e| matches!(e.as_str(), "truecolor" | "24bit"))
+}
 // ----------
 // ANSI CODES
 // ----------
 
-/// ANSI escape codes for text formatting
-#[derive(Clone, Copy)]
-#[repr(u8)]
+/// The colors of the text, as ANSI escape codes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Color {
-    Black = 30,
-    // Red,
-    // Green,
-    // Yellow,
-    // Blue,
-    // Magenta,
-    // Cyan,
-    White = 37,
-    // Default = 39,
-    // BgBlack = 40,
-    // BgRed,
-    // BgGreen,
-    // BgYellow,
-    // BgBlue,
-    // BgMagenta,
-    // BgCyan,
-    // BgWhite,
-    // BgDefault = 49,
-    // BrightBlack = 90,
-    // BrightRed,
-    // BrightGreen,
-    // BrightYellow,
-    // BrightBlue,
-    // BrightMagenta,
-    // BrightCyan,
-    // BrightWhite,
-    // BrightDefault = 99,
-    // BgBrightBlack = 100,
-    // BgBrightRed,
-    // BgBrightGreen,
-    // BgBrightYellow,
-    // BgBrightBlue,
-    // BgBrightMagenta,
-    // BgBrightCyan,
-    // BgBrightWhite,
-    // BgBrightDefault = 109,
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+    /// One of the 256 colors of the extended palette
+    Fixed(u8),
+    /// A 24-bit color; approximated by the extended palette where truecolor is not supported
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    /// Wrap the text in the escape codes for this color
+    pub fn paint(self, text: &str) -> String {
+        format!("\u{001b}[{}m{}\u{001b}[0m", self.code(), text)
+    }
+
+    /// The parameters of the escape code that sets this as the foreground color
+    pub fn code(self) -> String {
+        match self {
+            Color::Fixed(n) => format!("38;5;{}", n),
+            Color::Rgb(r, g, b) if is_truecolor_enabled() => format!("38;2;{};{};{}", r, g, b),
+            Color::Rgb(r, g, b) => format!("38;5;{}", to_ansi256(r, g, b)),
+            Color::Black => String::from("30"),
+            Color::Red => String::from("31"),
+            Color::Green => String::from("32"),
+            Color::Yellow => String::from("33"),
+            Color::Blue => String::from("34"),
+            Color::Magenta => String::from("35"),
+            Color::Cyan => String::from("36"),
+            Color::White => String::from("37"),
+            Color::BrightBlack => String::from("90"),
+            Color::BrightRed => String::from("91"),
+            Color::BrightGreen => String::from("92"),
+            Color::BrightYellow => String::from("93"),
+            Color::BrightBlue => String::from("94"),
+            Color::BrightMagenta => String::from("95"),
+            Color::BrightCyan => String::from("96"),
+            Color::BrightWhite => String::from("97"),
+        }
+    }
+}
+
+impl std::str::FromStr for Color {
+    type Err = String;
+
+    /// Parse a color from its name (e.g. `cyan` or `bright-black`), its index in the
+    /// 256-color palette (e.g. `117`) or its 24-bit hex code (e.g. `#8ec07c`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(hex) = s.strip_prefix('#') {
+            let channel = |i: usize| {
+                hex.get(i..i + 2)
+                    .and_then(|c| u8::from_str_radix(c, 16).ok())
+            };
+            return match (hex.len(), channel(0), channel(2), channel(4)) {
+                (6, Some(r), Some(g), Some(b)) => Ok(Color::Rgb(r, g, b)),
+                _ => Err(format!("Invalid hex color {:?}; expected #rrggbb", s)),
+            };
+        }
+        if let Ok(n) = s.parse::<u8>() {
+            return Ok(Color::Fixed(n));
+        }
+
+        let color = match s.to_lowercase().replace('_', "-").as_str() {
+            "black" => Color::Black,
+            "red" => Color::Red,
+            "green" => Color::Green,
+            "yellow" => Color::Yellow,
+            "blue" => Color::Blue,
+            "magenta" => Color::Magenta,
+            "cyan" => Color::Cyan,
+            "white" => Color::White,
+            "bright-black" | "gray" | "grey" => Color::BrightBlack,
+            "bright-red" => Color::BrightRed,
+            "bright-green" => Color::BrightGreen,
+            "bright-yellow" => Color::BrightYellow,
+            "bright-blue" => Color::BrightBlue,
+            "bright-magenta" => Color::BrightMagenta,
+            "bright-cyan" => Color::BrightCyan,
+            "bright-white" => Color::BrightWhite,
+            _ => return Err(format!("Unknown color {:?}", s)),
+        };
+        Ok(color)
+    }
+}
+
+/// Returns the closest color of the extended 256-color palette; either a shade of the
+/// grayscale ramp or a color of the 6x6x6 color cube
+pub fn to_ansi256(r: u8, g: u8, b: u8) -> u8 {
+    if r == g && g == b {
+        return match r {
+            0..=7 => 16,
+            239..=255 => 231,
+            _ => 232 + (r - 8) / 10,
+        };
+    }
+
+    let level = |c: u8| match c {
+        0..=47 => 0,
+        48..=114 => 1,
+        _ => (c - 35) / 40,
+    };
+    16 + 36 * level(r) + 6 * level(g) + level(b)
 }
 
 pub trait Colorable {
@@ -62,7 +147,7 @@ pub trait Colorable {
 impl Colorable for &'static str {
     fn ansi(&self, code: Color) -> String {
         if is_color_enabled() {
-            format!("\u{001b}[{}m{}\u{001b}[0m", code as u8, &self)
+            code.paint(self)
         } else {
             self.to_string()
         }
@@ -72,9 +157,32 @@ impl Colorable for &'static str {
 impl Colorable for String {
     fn ansi(&self, code: Color) -> String {
         if is_color_enabled() {
-            format!("\u{001b}[{}m{}\u{001b}[0m", code as u8, &self)
+            code.paint(self)
         } else {
             self.to_string()
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_approximate_rgb_colors_in_the_extended_palette() {
+        assert_eq!(to_ansi256(0, 0, 0), 16);
+        assert_eq!(to_ansi256(255, 255, 255), 231);
+        assert_eq!(to_ansi256(128, 128, 128), 244);
+        assert_eq!(to_ansi256(255, 0, 0), 196);
+        assert_eq!(to_ansi256(95, 135, 175), 67);
+    }
+
+    #[test]
+    fn should_parse_colors() {
+        assert_eq!("bright-black".parse(), Ok(Color::BrightBlack));
+        assert_eq!("117".parse(), Ok(Color::Fixed(117)));
+        assert_eq!("#8ec07c".parse(), Ok(Color::Rgb(0x8e, 0xc0, 0x7c)));
+        assert!("#8ec0".parse::<Color>().is_err());
+        assert!("octarine".parse::<Color>().is_err());
+    }
+}
@@ -0,0 +1,452 @@
+// Library
+use super::helpers;
+use std::ops::Range;
+use unicode_width::UnicodeWidthChar;
+
+// --------
+// ENCODING
+// --------
+
+/// The character encodings of the text column
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Encoding {
+    /// 7-bit ASCII
+    #[default]
+    Ascii,
+    /// ISO-8859-1
+    #[value(alias = "latin-1", alias = "iso-8859-1")]
+    Latin1,
+    /// The IBM PC character set, with graphic glyphs for the control bytes
+    #[value(alias = "dos", alias = "ibm437")]
+    Cp437,
+    /// EBCDIC (US/Canada)
+    #[value(alias = "ebcdic", alias = "ibm037")]
+    Cp037,
+    /// EBCDIC (Latin-1, as used by z/OS Unix)
+    #[value(alias = "ibm1047")]
+    Cp1047,
+    /// UTF-8; multi-byte characters span the cells of their bytes
+    #[value(alias = "utf-8")]
+    Utf8,
+    /// UTF-16 (little-endian)
+    #[value(alias = "utf-16le", alias = "utf16")]
+    Utf16le,
+    /// UTF-16 (big-endian)
+    #[value(alias = "utf-16be")]
+    Utf16be,
+}
+
+/// How a byte is shown in the text column, which has a cell for every byte
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Glyph {
+    /// A printable character that begins at this byte
+    Char(char),
+    /// A later byte of the multi-byte character before it (shown as a blank)
+    Continuation,
+    /// A later byte of the character before it, whose cell is taken up by that (wide) character
+    Covered,
+    /// A control character that begins at this byte (shown by its substitution glyph)
+    Control(char),
+    /// A byte that does not make up a character that can be shown (shown as a dot)
+    Substitute,
+}
+
+// ------------
+// SUBSTITUTION
+// ------------
+
+/// The glyphs that stand in for the control characters (and the bytes that cannot be shown)
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Substitution {
+    /// A dot for every character that cannot be shown
+    #[default]
+    Dot,
+    /// The Unicode control pictures (e.g. `␀`, `␊` and `␛`)
+    #[value(alias = "control-pictures")]
+    Pictures,
+    /// The caret notation (e.g. `^@`, `^J` and `^[`), which takes up two columns for every byte
+    Caret,
+}
+
+impl Substitution {
+    /// The number of columns that each byte takes up in the text column
+    pub fn width(self) -> usize {
+        match self {
+            Substitution::Caret => 2,
+            _ => 1,
+        }
+    }
+
+    /// The text of the glyph, which takes up exactly [`Substitution::width()`] columns (or none
+    /// for the cells that are covered by a wide character in single-column cells)
+    pub fn render(self, glyph: Glyph) -> String {
+        let text = match (self, glyph) {
+            (_, Glyph::Char(c)) => c.to_string(),
+            (_, Glyph::Continuation) => String::from(" "),
+            (Substitution::Caret, Glyph::Covered) => String::from("  "),
+            (_, Glyph::Covered) => String::new(),
+            (Substitution::Pictures, Glyph::Control(c @ '\0'..='\x1f')) => {
+                char::from_u32(0x2400 + c as u32).map_or(String::from("·"), String::from)
+            }
+            (Substitution::Pictures, Glyph::Control('\x7f')) => String::from("␡"),
+            (Substitution::Caret, Glyph::Control(c @ '\0'..='\x1f')) => {
+                format!("^{}", (c as u8 + 0x40) as char)
+            }
+            (Substitution::Caret, Glyph::Control('\x7f')) => String::from("^?"),
+            (_, Glyph::Control(_) | Glyph::Substitute) => String::from("·"),
+        };
+        // Pad the narrow glyphs out to the width of the cell
+        let width = text
+            .chars()
+            .filter_map(UnicodeWidthChar::width)
+            .sum::<usize>();
+        match (self.width(), glyph) {
+            (_, Glyph::Covered) => text,
+            (cell, _) => format!("{}{}", text, " ".repeat(cell.saturating_sub(width))),
+        }
+    }
+}
+
+impl Encoding {
+    /// The name of the encoding, as passed to `--encoding`
+    pub fn name(self) -> &'static str {
+        match self {
+            Encoding::Ascii => "ascii",
+            Encoding::Latin1 => "latin1",
+            Encoding::Cp437 => "cp437",
+            Encoding::Cp037 => "cp037",
+            Encoding::Cp1047 => "cp1047",
+            Encoding::Utf8 => "utf8",
+            Encoding::Utf16le => "utf16le",
+            Encoding::Utf16be => "utf16be",
+        }
+    }
+
+    /// The number of bytes in each code unit; characters begin on a multiple of it
+    pub fn unit(self) -> usize {
+        match self {
+            Encoding::Utf16le | Encoding::Utf16be => 2,
+            _ => 1,
+        }
+    }
+
+    /// The range of bytes to decode for the row, which includes enough bytes on either side to
+    /// complete the characters that cross into the row from the rows around it
+    pub fn window(self, row: Range<usize>) -> Range<usize> {
+        let context = match self {
+            Encoding::Utf8 | Encoding::Utf16le | Encoding::Utf16be => 3,
+            _ => 0,
+        };
+        let start = row.start.saturating_sub(context);
+        start - start % self.unit()..row.end + context
+    }
+
+    /// Decode the bytes into a glyph for each byte. Characters are decoded from the start of the
+    /// bytes, which must be aligned to a code unit
+    pub fn decode(self, bytes: &[u8]) -> Vec<Glyph> {
+        let table = match self {
+            Encoding::Ascii => {
+                return bytes
+                    .iter()
+                    .map(|byte| match helpers::is_printable_ascii_character(byte) {
+                        true => Glyph::Char(*byte as char),
+                        false if byte.is_ascii() => Glyph::Control(*byte as char),
+                        false => Glyph::Substitute,
+                    })
+                    .collect()
+            }
+            Encoding::Utf8 => return decode_utf8(bytes),
+            Encoding::Utf16le => return decode_utf16(bytes, u16::from_le_bytes),
+            Encoding::Utf16be => return decode_utf16(bytes, u16::from_be_bytes),
+            Encoding::Latin1 => None,
+            Encoding::Cp437 => Some(&CP437),
+            Encoding::Cp037 => Some(&CP037),
+            Encoding::Cp1047 => Some(&CP1047),
+        };
+        let mut glyphs = Vec::with_capacity(bytes.len());
+        for &byte in bytes {
+            let c = table.map_or(byte as char, |table| table[byte as usize]);
+            push_char(&mut glyphs, c, 1);
+        }
+        glyphs
+    }
+
+    /// Encode the character into its bytes, or `None` if the encoding cannot represent it
+    pub fn encode(self, c: char) -> Option<Vec<u8>> {
+        let table = match self {
+            Encoding::Ascii => return c.is_ascii().then(|| vec![c as u8]),
+            Encoding::Latin1 => return u8::try_from(c).ok().map(|byte| vec![byte]),
+            Encoding::Utf8 => return Some(c.to_string().into_bytes()),
+            Encoding::Utf16le => {
+                return Some(
+                    c.encode_utf16(&mut [0; 2])
+                        .iter()
+                        .flat_map(|u| u.to_le_bytes())
+                        .collect(),
+                )
+            }
+            Encoding::Utf16be => {
+                return Some(
+                    c.encode_utf16(&mut [0; 2])
+                        .iter()
+                        .flat_map(|u| u.to_be_bytes())
+                        .collect(),
+                )
+            }
+            Encoding::Cp437 => &CP437,
+            Encoding::Cp037 => &CP037,
+            Encoding::Cp1047 => &CP1047,
+        };
+        table
+            .iter()
+            .position(|&t| t == c)
+            .map(|byte| vec![byte as u8])
+    }
+
+    /// Decode the bytes of a row, given the `window` of bytes around it (see [`Encoding::window()`])
+    /// and the range of the row within the window. Returns a glyph for each byte of the row.
+    ///
+    /// The glyphs take up exactly one column per byte, so that the rows stay aligned.
+    pub fn decode_row(self, window: &[u8], row: Range<usize>) -> Vec<Glyph> {
+        let glyphs = self.decode(window);
+        let mut row_glyphs = glyphs[row.clone()].to_vec();
+        // The character that covers the first cell is shown on the row before
+        if let Some(first @ Glyph::Covered) = row_glyphs.first_mut() {
+            *first = Glyph::Continuation;
+        }
+        // A wide character in the last cell would stick out of the row
+        if glyphs.get(row.end) == Some(&Glyph::Covered) {
+            if let Some(last) = row_glyphs.last_mut() {
+                *last = Glyph::Substitute;
+            }
+        }
+        row_glyphs
+    }
+}
+
+/// Push the glyphs of the character that was decoded from `len` bytes
+fn push_char(glyphs: &mut Vec<Glyph>, c: char, len: usize) {
+    let width = c.width().unwrap_or(0);
+    if c.is_control() {
+        glyphs.push(Glyph::Control(c));
+        glyphs.extend(std::iter::repeat_n(Glyph::Continuation, len - 1));
+        return;
+    }
+    if width == 0 || width > len {
+        glyphs.push(Glyph::Substitute);
+        glyphs.extend(std::iter::repeat_n(Glyph::Continuation, len - 1));
+        return;
+    }
+    glyphs.push(Glyph::Char(c));
+    glyphs.extend(std::iter::repeat_n(Glyph::Covered, width - 1));
+    glyphs.extend(std::iter::repeat_n(Glyph::Continuation, len - width));
+}
+
+/// Decode UTF-8, substituting every byte that is not part of a valid sequence
+fn decode_utf8(bytes: &[u8]) -> Vec<Glyph> {
+    let mut glyphs = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let len = match bytes[i] {
+            0x00..=0x7f => 1,
+            0xc2..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf4 => 4,
+            _ => 0,
+        };
+        // Rejects the truncated sequences, along with the overlong encodings and surrogates
+        let c = bytes
+            .get(i..i + len)
+            .and_then(|sequence| std::str::from_utf8(sequence).ok())
+            .and_then(|s| s.chars().next());
+        match c {
+            Some(c) => {
+                push_char(&mut glyphs, c, len);
+                i += len;
+            }
+            None => {
+                glyphs.push(Glyph::Substitute);
+                i += 1;
+            }
+        }
+    }
+    glyphs
+}
+
+/// Decode UTF-16 with the given byte-order, substituting the unpaired surrogates
+fn decode_utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> Vec<Glyph> {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| unit([pair[0], pair[1]]))
+        .collect();
+    let mut glyphs = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < units.len() {
+        let len = match units[i] {
+            0xd800..=0xdbff if matches!(units.get(i + 1), Some(0xdc00..=0xdfff)) => 2,
+            _ => 1,
+        };
+        match char::decode_utf16(units[i..i + len].iter().copied()).next() {
+            Some(Ok(c)) => push_char(&mut glyphs, c, len * 2),
+            _ => glyphs.extend([Glyph::Substitute, Glyph::Continuation]),
+        }
+        i += len;
+    }
+    // A trailing odd byte is half of a code unit
+    glyphs.resize(bytes.len(), Glyph::Substitute);
+    glyphs
+}
+
+/// The characters of code page 437 (the original IBM PC character set), with the graphic glyphs
+/// of its control bytes
+const CP437: [char; 256] = [
+    '\u{0}', '☺', '☻', '♥', '♦', '♣', '♠', '•', '◘', '○', '◙', '♂', '♀', '♪', '♫', '☼', '►', '◄',
+    '↕', '‼', '¶', '§', '▬', '↨', '↑', '↓', '→', '←', '∟', '↔', '▲', '▼', ' ', '!', '"', '#', '$',
+    '%', '&', '\'', '(', ')', '*', '+', ',', '-', '.', '/', '0', '1', '2', '3', '4', '5', '6', '7',
+    '8', '9', ':', ';', '<', '=', '>', '?', '@', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J',
+    'K', 'L', 'M', 'N', 'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', '[', '\\', ']',
+    '^', '_', '`', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o', 'p',
+    'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z', '{', '|', '}', '~', '⌂', 'Ç', 'ü', 'é', 'â',
+    'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å', 'É', 'æ', 'Æ', 'ô', 'ö', 'ò', 'û',
+    'ù', 'ÿ', 'Ö', 'Ü', '¢', '£', '¥', '₧', 'ƒ', 'á', 'í', 'ó', 'ú', 'ñ', 'Ñ', 'ª', 'º', '¿', '⌐',
+    '¬', '½', '¼', '¡', '«', '»', '░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕', '╣', '║', '╗', '╝',
+    '╜', '╛', '┐', '└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩', '╦', '╠', '═', '╬', '╧',
+    '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘', '┌', '█', '▄', '▌', '▐', '▀', 'α', 'ß', 'Γ',
+    'π', 'Σ', 'σ', 'µ', 'τ', 'Φ', 'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩', '≡', '±', '≥', '≤', '⌠', '⌡',
+    '÷', '≈', '°', '∙', '·', '√', 'ⁿ', '²', '■', '\u{a0}',
+];
+
+/// The characters of EBCDIC code page 037 (US/Canada)
+const CP037: [char; 256] = [
+    '\u{0}', '\u{1}', '\u{2}', '\u{3}', '\u{9c}', '\u{9}', '\u{86}', '\u{7f}', '\u{97}', '\u{8d}',
+    '\u{8e}', '\u{b}', '\u{c}', '\u{d}', '\u{e}', '\u{f}', '\u{10}', '\u{11}', '\u{12}', '\u{13}',
+    '\u{9d}', '\u{85}', '\u{8}', '\u{87}', '\u{18}', '\u{19}', '\u{92}', '\u{8f}', '\u{1c}',
+    '\u{1d}', '\u{1e}', '\u{1f}', '\u{80}', '\u{81}', '\u{82}', '\u{83}', '\u{84}', '\u{a}',
+    '\u{17}', '\u{1b}', '\u{88}', '\u{89}', '\u{8a}', '\u{8b}', '\u{8c}', '\u{5}', '\u{6}',
+    '\u{7}', '\u{90}', '\u{91}', '\u{16}', '\u{93}', '\u{94}', '\u{95}', '\u{96}', '\u{4}',
+    '\u{98}', '\u{99}', '\u{9a}', '\u{9b}', '\u{14}', '\u{15}', '\u{9e}', '\u{1a}', ' ', '\u{a0}',
+    'â', 'ä', 'à', 'á', 'ã', 'å', 'ç', 'ñ', '¢', '.', '<', '(', '+', '|', '&', 'é', 'ê', 'ë', 'è',
+    'í', 'î', 'ï', 'ì', 'ß', '!', '$', '*', ')', ';', '¬', '-', '/', 'Â', 'Ä', 'À', 'Á', 'Ã', 'Å',
+    'Ç', 'Ñ', '¦', ',', '%', '_', '>', '?', 'ø', 'É', 'Ê', 'Ë', 'È', 'Í', 'Î', 'Ï', 'Ì', '`', ':',
+    '#', '@', '\'', '=', '"', 'Ø', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', '«', '»', 'ð', 'ý',
+    'þ', '±', '°', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 'ª', 'º', 'æ', '¸', 'Æ', '¤', 'µ',
+    '~', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z', '¡', '¿', 'Ð', 'Ý', 'Þ', '®', '^', '£', '¥', '·',
+    '©', '§', '¶', '¼', '½', '¾', '[', ']', '¯', '¨', '´', '×', '{', 'A', 'B', 'C', 'D', 'E', 'F',
+    'G', 'H', 'I', '\u{ad}', 'ô', 'ö', 'ò', 'ó', 'õ', '}', 'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q',
+    'R', '¹', 'û', 'ü', 'ù', 'ú', 'ÿ', '\\', '÷', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', '²', 'Ô',
+    'Ö', 'Ò', 'Ó', 'Õ', '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '³', 'Û', 'Ü', 'Ù', 'Ú',
+    '\u{9f}',
+];
+
+/// The characters of EBCDIC code page 1047 (Latin-1, as used by z/OS Unix)
+const CP1047: [char; 256] = [
+    '\u{0}', '\u{1}', '\u{2}', '\u{3}', '\u{9c}', '\u{9}', '\u{86}', '\u{7f}', '\u{97}', '\u{8d}',
+    '\u{8e}', '\u{b}', '\u{c}', '\u{d}', '\u{e}', '\u{f}', '\u{10}', '\u{11}', '\u{12}', '\u{13}',
+    '\u{9d}', '\u{a}', '\u{8}', '\u{87}', '\u{18}', '\u{19}', '\u{92}', '\u{8f}', '\u{1c}',
+    '\u{1d}', '\u{1e}', '\u{1f}', '\u{80}', '\u{81}', '\u{82}', '\u{83}', '\u{84}', '\u{85}',
+    '\u{17}', '\u{1b}', '\u{88}', '\u{89}', '\u{8a}', '\u{8b}', '\u{8c}', '\u{5}', '\u{6}',
+    '\u{7}', '\u{90}', '\u{91}', '\u{16}', '\u{93}', '\u{94}', '\u{95}', '\u{96}', '\u{4}',
+    '\u{98}', '\u{99}', '\u{9a}', '\u{9b}', '\u{14}', '\u{15}', '\u{9e}', '\u{1a}', ' ', '\u{a0}',
+    'â', 'ä', 'à', 'á', 'ã', 'å', 'ç', 'ñ', '¢', '.', '<', '(', '+', '|', '&', 'é', 'ê', 'ë', 'è',
+    'í', 'î', 'ï', 'ì', 'ß', '!', '$', '*', ')', ';', '^', '-', '/', 'Â', 'Ä', 'À', 'Á', 'Ã', 'Å',
+    'Ç', 'Ñ', '¦', ',', '%', '_', '>', '?', 'ø', 'É', 'Ê', 'Ë', 'È', 'Í', 'Î', 'Ï', 'Ì', '`', ':',
+    '#', '@', '\'', '=', '"', 'Ø', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', '«', '»', 'ð', 'ý',
+    'þ', '±', '°', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 'ª', 'º', 'æ', '¸', 'Æ', '¤', 'µ',
+    '~', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z', '¡', '¿', 'Ð', '[', 'Þ', '®', '¬', '£', '¥', '·',
+    '©', '§', '¶', '¼', '½', '¾', 'Ý', '¨', '¯', ']', '´', '×', '{', 'A', 'B', 'C', 'D', 'E', 'F',
+    'G', 'H', 'I', '\u{ad}', 'ô', 'ö', 'ò', 'ó', 'õ', '}', 'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q',
+    'R', '¹', 'û', 'ü', 'ù', 'ú', 'ÿ', '\\', '÷', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', '²', 'Ô',
+    'Ö', 'Ò', 'Ó', 'Õ', '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '³', 'Û', 'Ü', 'Ù', 'Ú',
+    '\u{9f}',
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(encoding: Encoding, bytes: &[u8]) -> String {
+        encoding
+            .decode(bytes)
+            .iter()
+            .map(|glyph| Substitution::Dot.render(*glyph))
+            .collect()
+    }
+
+    #[test]
+    fn should_decode_single_byte_encodings() {
+        assert_eq!(
+            text(Encoding::Cp037, &[0xc8, 0x85, 0x93, 0x93, 0x96, 0x25]),
+            "Hello·"
+        );
+        assert_eq!(text(Encoding::Cp1047, &[0x5f, 0xad, 0xbd, 0x15]), "^[]·");
+        assert_eq!(text(Encoding::Cp437, &[0x01, 0x00, 0xdb, 0x7f]), "☺·█⌂");
+        assert_eq!(text(Encoding::Latin1, &[0xe9, 0x85, b'a']), "é·a");
+    }
+
+    #[test]
+    fn should_substitute_control_characters() {
+        let glyphs = Encoding::Ascii.decode(&[b'a', 0x00, 0x1b, b' ', 0x7f, 0x80]);
+        let render = |substitution: Substitution| -> String {
+            glyphs
+                .iter()
+                .map(|glyph| substitution.render(*glyph))
+                .collect()
+        };
+        assert_eq!(render(Substitution::Dot), "a·· ··");
+        assert_eq!(render(Substitution::Pictures), "a␀␛ ␡·");
+        assert_eq!(render(Substitution::Caret), "a ^@^[  ^?· ");
+        assert_eq!(Substitution::Caret.render(Glyph::Char('中')), "中");
+    }
+
+    #[test]
+    fn should_encode_characters() {
+        assert_eq!(Encoding::Cp037.encode('H'), Some(vec![0xc8]));
+        assert_eq!(Encoding::Cp437.encode('☺'), Some(vec![0x01]));
+        assert_eq!(Encoding::Utf16be.encode('é'), Some(vec![0x00, 0xe9]));
+        assert_eq!(Encoding::Ascii.encode('é'), None);
+    }
+
+    #[test]
+    fn should_span_multi_byte_characters() {
+        use Glyph::*;
+        assert_eq!(
+            Encoding::Utf8.decode("aé中".as_bytes()),
+            vec![
+                Char('a'),
+                Char('é'),
+                Continuation,
+                Char('中'),
+                Covered,
+                Continuation
+            ]
+        );
+        assert_eq!(text(Encoding::Utf8, &[0xc3, b'a', 0xff]), "·a·");
+        assert_eq!(
+            text(Encoding::Utf16le, &[b'h', 0, b'i', 0, 0x3d, 0xd8]),
+            "h i · "
+        );
+        assert_eq!(
+            text(Encoding::Utf16be, &[0, b'h', 0xd8, 0x3d, 0xde, 0x00, 0]),
+            "h 😀  ·"
+        );
+    }
+
+    #[test]
+    fn should_decode_characters_across_rows() {
+        let data = "abcdé中".as_bytes(); // The `é` crosses from the first row of 5 into the second
+        let window = Encoding::Utf8.window(5..9);
+        assert_eq!(window, 2..12);
+        let glyphs = Encoding::Utf8.decode_row(&data[2..], 3..7);
+        assert_eq!(
+            glyphs,
+            vec![
+                Glyph::Continuation,
+                Glyph::Char('中'),
+                Glyph::Covered,
+                Glyph::Continuation
+            ]
+        );
+        let glyphs = Encoding::Utf8.decode_row(data, 0..5);
+        assert_eq!(glyphs.last(), Some(&Glyph::Char('é')));
+    }
+}
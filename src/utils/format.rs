@@ -1,3 +1,6 @@
+// Library
+use crate::error::Error;
+
 // -------
 // FORMATS
 // -------
@@ -15,12 +18,28 @@ pub enum Format {
     Octal,
     OctalWithPrefix,
     Decimal,
+    /// Multi-byte unsigned integer of the given number of bytes (e.g. 2 for `u16`)
+    Unsigned(usize, Endianness),
+    /// Multi-byte signed integer of the given number of bytes (e.g. 2 for `i16`)
+    Signed(usize, Endianness),
+    /// IEEE-754 floating point number of the given number of bytes (e.g. 4 for `f32`)
+    Float(usize, Endianness),
+}
+
+/// The byte-order of multi-byte values
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
 }
 
 // Parse Format enum from a string
-impl From<String> for Format {
-    fn from(format_string: String) -> Self {
-        match format_string.as_ref() {
+impl std::str::FromStr for Format {
+    type Err = Error;
+
+    fn from_str(format_string: &str) -> Result<Self, Self::Err> {
+        Ok(match format_string {
             "hex" | "x" | "hexadecimal" => Format::Hex,
             "#hex" | "#x" | "#hexadecimal" => Format::HexWithPrefix,
             "HEX" | "X" | "Hex" | "Hexadecimal" => Format::UpperHex,
@@ -30,8 +49,35 @@ impl From<String> for Format {
             "octal" | "o" | "oct" => Format::Octal,
             "#octal" | "#o" | "#oct" => Format::OctalWithPrefix,
             "decimal" | "d" | "dec" => Format::Decimal,
-            _ => panic!("Invalid output format"),
-        }
+            s => parse_multibyte(s).ok_or_else(|| Error::InvalidFormat(s.to_string()))?,
+        })
+    }
+}
+
+/// Parse a multi-byte format like `u16`, `i32le`, `u64be` or `f32`
+fn parse_multibyte(s: &str) -> Option<Format> {
+    // Split off the byte-order suffix (defaults to little-endian)
+    let (s, endianness) = if let Some(s) = s.strip_suffix("le") {
+        (s, Endianness::Little)
+    } else if let Some(s) = s.strip_suffix("be") {
+        (s, Endianness::Big)
+    } else {
+        (s, Endianness::Little)
+    };
+
+    let bytes = match &s[1.min(s.len())..] {
+        "16" => 2,
+        "32" => 4,
+        "64" => 8,
+        "128" => 16,
+        _ => return None,
+    };
+
+    match s.chars().next() {
+        Some('u') => Some(Format::Unsigned(bytes, endianness)),
+        Some('i') => Some(Format::Signed(bytes, endianness)),
+        Some('f') if bytes <= 8 => Some(Format::Float(bytes, endianness)),
+        _ => None,
     }
 }
 
@@ -48,6 +94,91 @@ impl Format {
             Self::Octal => format!("{:03o}", data),               // e.g. 077
             Self::OctalWithPrefix => format!("{:#05o}", data),    // e.g. 0o077
             Self::Decimal => format!("{:03}", data),              // e.g. 063
+            Self::Unsigned(..) | Self::Signed(..) | Self::Float(..) => self.format_bytes(&[data]),
+        }
+    }
+
+    /// Formats a single value made up of the given bytes.
+    ///
+    /// Byte formats only look at the first byte, while multi-byte formats read up to
+    /// [`Format::bytes()`] bytes; missing bytes are treated as zeros.
+    pub fn format_bytes(&self, data: &[u8]) -> String {
+        match &self {
+            Self::Unsigned(bytes, endianness) => {
+                let value = to_u128(data, *bytes, *endianness);
+                format!("{:>1$}", value, self.size()) // e.g.   513
+            }
+            Self::Signed(bytes, endianness) => {
+                // Sign-extend the value from its actual width
+                let shift = 128 - bytes * 8;
+                let value = ((to_u128(data, *bytes, *endianness) << shift) as i128) >> shift;
+                format!("{:>1$}", value, self.size()) // e.g.  -513
+            }
+            Self::Float(bytes, endianness) => {
+                let bits = to_u128(data, *bytes, *endianness);
+                let value = match bytes {
+                    2 => f16_to_f64(bits as u16),
+                    4 => f32::from_bits(bits as u32) as f64,
+                    _ => f64::from_bits(bits as u64),
+                };
+                // Use scientific notation with the precision of the type, so that every value has
+                // the same width (NaN and infinities are right-aligned to the same width)
+                let precision = match bytes {
+                    2 => 3,
+                    4 => 6,
+                    _ => 15,
+                };
+                format!("{:>1$.2$e}", value, self.size(), precision) // e.g.  1.500000e0
+            }
+            _ => self.format(data.first().copied().unwrap_or_default()),
+        }
+    }
+
+    /// Parses a value formatted by [`Format::format_bytes()`] back into the bytes it represents.
+    ///
+    /// Prefixes are optional for the radix formats. Returns `None` if the value is not valid for
+    /// this format. Floating point values are shown with limited precision and cannot be parsed back.
+    pub fn parse_bytes(&self, value: &str) -> Option<Vec<u8>> {
+        let value = value.trim();
+        let byte = |prefix: &str, radix: u32| {
+            let digits = strip_prefix(value, prefix);
+            u8::from_str_radix(digits, radix).ok().map(|b| vec![b])
+        };
+        match &self {
+            Self::Hex | Self::HexWithPrefix | Self::UpperHex | Self::UpperHexWithPrefix => {
+                byte("0x", 16)
+            }
+            Self::Binary | Self::BinaryWithPrefix => byte("0b", 2),
+            Self::Octal | Self::OctalWithPrefix => byte("0o", 8),
+            Self::Decimal => byte("", 10),
+            Self::Unsigned(bytes, endianness) => {
+                let v: u128 = value.parse().ok()?;
+                // Reject values that do not fit in the width
+                if *bytes < 16 && v >> (bytes * 8) != 0 {
+                    return None;
+                }
+                Some(from_u128(v, *bytes, *endianness))
+            }
+            Self::Signed(bytes, endianness) => {
+                let v: i128 = value.parse().ok()?;
+                // Reject values that do not survive sign-extension from the width
+                let shift = 128 - bytes * 8;
+                if (v << shift) >> shift != v {
+                    return None;
+                }
+                Some(from_u128(v as u128, *bytes, *endianness))
+            }
+            Self::Float(..) => None,
+        }
+    }
+
+    /// Returns the number of bytes that make up each formatted value
+    pub fn bytes(&self) -> usize {
+        match &self {
+            Format::Unsigned(bytes, _) | Format::Signed(bytes, _) | Format::Float(bytes, _) => {
+                *bytes
+            }
+            _ => 1,
         }
     }
 
@@ -63,10 +194,113 @@ impl Format {
             Format::Octal => 3,              // e.g. 077
             Format::OctalWithPrefix => 5,    // e.g. 0o077
             Format::Decimal => 3,            // e.g. 063
+            Format::Unsigned(bytes, _) => match bytes {
+                2 => 5,  // e.g. 65535
+                4 => 10, // e.g. 4294967295
+                8 => 20, // e.g. 18446744073709551615
+                _ => 39, // e.g. 340282366920938463463374607431768211455
+            },
+            Format::Signed(bytes, _) => match bytes {
+                2 => 6,  // e.g. -32768
+                4 => 11, // e.g. -2147483648
+                8 => 20, // e.g. -9223372036854775808
+                _ => 40, // e.g. -170141183460469231731687303715884105728
+            },
+            Format::Float(bytes, _) => match bytes {
+                2 => 9,  // e.g. -5.960e-8
+                4 => 13, // e.g. -1.401298e-45
+                _ => 23, // e.g. -2.225073858507201e-308
+            },
         }
     }
 }
 
+/// Reads up to `bytes` bytes of `data` as an unsigned integer with the given byte-order
+fn to_u128(data: &[u8], bytes: usize, endianness: Endianness) -> u128 {
+    let data = (0..bytes).map(|i| data.get(i).copied().unwrap_or_default());
+    match endianness {
+        Endianness::Little => data.rev().fold(0, |acc, b| (acc << 8) | b as u128),
+        Endianness::Big => data.fold(0, |acc, b| (acc << 8) | b as u128),
+    }
+}
+
+/// Writes the lowest `bytes` bytes of the value in the given byte-order
+fn from_u128(value: u128, bytes: usize, endianness: Endianness) -> Vec<u8> {
+    let data = (0..bytes).map(|i| (value >> (i * 8)) as u8);
+    match endianness {
+        Endianness::Little => data.collect(),
+        Endianness::Big => data.rev().collect(),
+    }
+}
+
+/// Strips the (case-insensitive) prefix from the value if it is present
+fn strip_prefix<'a>(value: &'a str, prefix: &str) -> &'a str {
+    match value.get(..prefix.len()) {
+        Some(p) if !prefix.is_empty() && p.eq_ignore_ascii_case(prefix) => &value[prefix.len()..],
+        _ => value,
+    }
+}
+
+/// Converts the bits of an IEEE-754 half-precision float to a double
+fn f16_to_f64(bits: u16) -> f64 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((bits >> 10) & 0x1f) as i32;
+    let mantissa = (bits & 0x3ff) as f64;
+    match exponent {
+        0 => sign * mantissa * 2f64.powi(-24), // Zero and subnormal numbers
+        0x1f if mantissa == 0.0 => sign * f64::INFINITY,
+        0x1f => f64::NAN,
+        _ => sign * (1.0 + mantissa / 1024.0) * 2f64.powi(exponent - 15),
+    }
+}
+
+// -------
+// OFFSETS
+// -------
+
+/// The minimum number of digits in the offset column
+const MIN_OFFSET_WIDTH: usize = 8;
+
+/// The radix in which to display the offset column
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Radix {
+    #[default]
+    #[value(alias = "x")]
+    Hex,
+    #[value(alias = "d")]
+    Decimal,
+    #[value(alias = "o")]
+    Octal,
+}
+
+impl Radix {
+    /// Format the offset in this radix (without any padding)
+    pub fn format(&self, offset: u64) -> String {
+        match self {
+            Radix::Hex => format!("{:x}", offset),
+            Radix::Decimal => format!("{}", offset),
+            Radix::Octal => format!("{:o}", offset),
+        }
+    }
+
+    /// Parse an offset formatted in this radix
+    pub fn parse(&self, s: &str) -> Option<u64> {
+        let radix = match self {
+            Radix::Hex => 16,
+            Radix::Decimal => 10,
+            Radix::Octal => 8,
+        };
+        u64::from_str_radix(s, radix).ok()
+    }
+
+    /// The number of digits needed to display every offset up to the given one.
+    ///
+    /// The offset column is at least 8 digits wide, and widens to fit larger offsets.
+    pub fn width(&self, max_offset: u64) -> usize {
+        std::cmp::max(MIN_OFFSET_WIDTH, self.format(max_offset).len())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -118,6 +352,171 @@ mod tests {
         assert_eq!(Format::Decimal.format(TEST_INPUT), "063");
     }
 
+    #[test]
+    fn should_parse_integer_formats() {
+        assert!(matches!(
+            "u16".parse::<Format>().unwrap(),
+            Format::Unsigned(2, Endianness::Little)
+        ));
+        assert!(matches!(
+            "i32be".parse::<Format>().unwrap(),
+            Format::Signed(4, Endianness::Big)
+        ));
+        assert!(matches!(
+            "u128le".parse::<Format>().unwrap(),
+            Format::Unsigned(16, Endianness::Little)
+        ));
+    }
+
+    #[test]
+    fn should_reject_unknown_formats() {
+        assert!(matches!(
+            "hexx".parse::<Format>(),
+            Err(Error::InvalidFormat(f)) if f == "hexx"
+        ));
+        assert!("u24".parse::<Format>().is_err());
+        assert!("f128".parse::<Format>().is_err());
+    }
+
+    #[test]
+    fn should_format_as_unsigned_integers() {
+        let data = [0x01, 0x02, 0x03, 0x04];
+        assert_eq!(
+            Format::Unsigned(2, Endianness::Little).format_bytes(&data),
+            "  513"
+        );
+        assert_eq!(
+            Format::Unsigned(2, Endianness::Big).format_bytes(&data),
+            "  258"
+        );
+        assert_eq!(
+            Format::Unsigned(4, Endianness::Little).format_bytes(&data),
+            "  67305985"
+        );
+        assert_eq!(
+            Format::Unsigned(4, Endianness::Big).format_bytes(&data),
+            "  16909060"
+        );
+    }
+
+    #[test]
+    fn should_format_as_signed_integers() {
+        let data = [0xfe, 0xff, 0xff, 0xff];
+        assert_eq!(
+            Format::Signed(2, Endianness::Little).format_bytes(&data),
+            "    -2"
+        );
+        assert_eq!(
+            Format::Signed(2, Endianness::Big).format_bytes(&data),
+            "  -257"
+        );
+        assert_eq!(
+            Format::Signed(4, Endianness::Little).format_bytes(&data),
+            "         -2"
+        );
+        assert_eq!(
+            Format::Signed(16, Endianness::Little).format_bytes(&[0xff; 16]),
+            format!("{:>40}", -1)
+        );
+    }
+
+    #[test]
+    fn should_parse_float_formats() {
+        assert!(matches!(
+            "f16".parse::<Format>().unwrap(),
+            Format::Float(2, Endianness::Little)
+        ));
+        assert!(matches!(
+            "f64be".parse::<Format>().unwrap(),
+            Format::Float(8, Endianness::Big)
+        ));
+    }
+
+    #[test]
+    fn should_format_as_floats() {
+        let f16 = Format::Float(2, Endianness::Little);
+        assert_eq!(f16.format_bytes(&[0x00, 0x3e]), "  1.500e0");
+        assert_eq!(f16.format_bytes(&[0x01, 0x00]), " 5.960e-8");
+        assert_eq!(f16.format_bytes(&[0x00, 0xfc]), "     -inf");
+        assert_eq!(f16.format_bytes(&[0x01, 0x7e]), "      NaN");
+
+        let f32 = Format::Float(4, Endianness::Big);
+        assert_eq!(f32.format_bytes(&1.5f32.to_be_bytes()), "   1.500000e0");
+        assert_eq!(f32.format_bytes(&f32::MIN.to_be_bytes()), " -3.402823e38");
+        assert_eq!(
+            f32.format_bytes(&f32::INFINITY.to_be_bytes()),
+            "          inf"
+        );
+
+        let f64 = Format::Float(8, Endianness::Little);
+        assert_eq!(
+            f64.format_bytes(&(-0.1f64).to_le_bytes()),
+            "  -1.000000000000000e-1"
+        );
+    }
+
+    #[test]
+    fn should_parse_formatted_bytes_back() {
+        let formats = [
+            Format::Hex,
+            Format::HexWithPrefix,
+            Format::UpperHex,
+            Format::UpperHexWithPrefix,
+            Format::Binary,
+            Format::BinaryWithPrefix,
+            Format::Octal,
+            Format::OctalWithPrefix,
+            Format::Decimal,
+        ];
+        for format in formats {
+            for byte in [0u8, TEST_INPUT, 255] {
+                assert_eq!(format.parse_bytes(&format.format(byte)), Some(vec![byte]));
+            }
+        }
+
+        let data = [0xfe, 0xff, 0x01, 0x80];
+        for format in [
+            Format::Unsigned(4, Endianness::Little),
+            Format::Unsigned(2, Endianness::Big),
+            Format::Signed(4, Endianness::Big),
+            Format::Signed(2, Endianness::Little),
+        ] {
+            let bytes = &data[..format.bytes()];
+            let value = format.format_bytes(bytes);
+            assert_eq!(format.parse_bytes(&value).as_deref(), Some(bytes));
+        }
+    }
+
+    #[test]
+    fn should_reject_invalid_values() {
+        assert_eq!(Format::Hex.parse_bytes("3g"), None);
+        assert_eq!(Format::Hex.parse_bytes("100"), None);
+        assert_eq!(
+            Format::Unsigned(2, Endianness::Little).parse_bytes("65536"),
+            None
+        );
+        assert_eq!(
+            Format::Signed(2, Endianness::Little).parse_bytes("-32769"),
+            None
+        );
+        assert_eq!(
+            Format::Float(4, Endianness::Little).parse_bytes("1.5e0"),
+            None
+        );
+    }
+
+    #[test]
+    fn should_treat_missing_bytes_as_zeros() {
+        assert_eq!(
+            Format::Unsigned(4, Endianness::Little).format_bytes(&[0x01, 0x01]),
+            format!("{:>10}", 257)
+        );
+        assert_eq!(
+            Format::Unsigned(4, Endianness::Big).format_bytes(&[0x01]),
+            format!("{:>10}", 16777216)
+        );
+    }
+
     #[test]
     fn should_have_appropriate_size() {
         assert_eq!(Format::Hex.size(), "3f".len());
@@ -129,5 +528,46 @@ mod tests {
         assert_eq!(Format::Octal.size(), "077".len());
         assert_eq!(Format::OctalWithPrefix.size(), "0o077".len());
         assert_eq!(Format::Decimal.size(), "063".len());
+        assert_eq!(
+            Format::Unsigned(2, Endianness::Little).size(),
+            "65535".len()
+        );
+        assert_eq!(
+            Format::Signed(8, Endianness::Big).size(),
+            i64::MIN.to_string().len()
+        );
+        assert_eq!(
+            Format::Unsigned(16, Endianness::Big).size(),
+            u128::MAX.to_string().len()
+        );
+        assert_eq!(
+            Format::Signed(16, Endianness::Big).size(),
+            i128::MIN.to_string().len()
+        );
+        assert_eq!(
+            Format::Float(4, Endianness::Little).size(),
+            format!("{:.6e}", f32::from_bits(0x8000_0001)).len()
+        );
+        assert_eq!(
+            Format::Float(8, Endianness::Little).size(),
+            format!("{:.15e}", -f64::MIN_POSITIVE).len()
+        );
+    }
+
+    #[test]
+    fn should_format_and_parse_offsets_in_each_radix() {
+        assert_eq!(Radix::Hex.format(0x1_0000_0000), "100000000");
+        assert_eq!(Radix::Decimal.format(300), "300");
+        assert_eq!(Radix::Octal.format(300), "454");
+        assert_eq!(Radix::Hex.parse("1f0"), Some(0x1f0));
+        assert_eq!(Radix::Octal.parse("9"), None);
+    }
+
+    #[test]
+    fn should_widen_the_offset_column_for_large_offsets() {
+        assert_eq!(Radix::Hex.width(0), 8);
+        assert_eq!(Radix::Hex.width(0xffff_ffff), 8);
+        assert_eq!(Radix::Hex.width(0x1_0000_0000), 9);
+        assert_eq!(Radix::Decimal.width(u64::MAX), 20);
     }
 }
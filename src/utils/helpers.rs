@@ -1,55 +1,259 @@
 // Library
-use std::io::Seek;
+use std::io::{Read, Seek};
+
+use super::source::Mapped;
+use crate::error::{Error, Result};
 
 // -------
 // HELPERS
 // -------
 
 /// Returns a boolean indicating whether the given byte represents
-/// a printable ascii character.
+/// a printable ascii character (`' '` to `'~'`); i.e. neither a control character nor a byte
+/// with the high bit set, which would otherwise be written to the terminal raw.
 pub fn is_printable_ascii_character(byte: &u8) -> bool {
-    byte.is_ascii_graphic() || !byte.is_ascii_whitespace()
+    byte.is_ascii_graphic() || *byte == b' '
 }
 
+// ------
 // READER
 // ------
 
+/// Reads from the reader until the buffer is full or the end of the input is reached.
+///
+/// Unlike a single call to `read`, this never returns a partially filled buffer
+/// unless there is no more data left. Returns the number of bytes read.
+pub fn read_chunk<T>(reader: &mut T, buffer: &mut [u8]) -> std::io::Result<usize>
+where
+    T: std::io::Read,
+{
+    let mut total = 0;
+    while total < buffer.len() {
+        match reader.read(&mut buffer[total..]) {
+            Ok(0) => break,
+            Ok(n) => total += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(total)
+}
+
+/// Resolves the offset against the size of the input, and returns the position it points to.
+///
+/// A positive offset counts forwards from the start, while a negative offset counts backwards
+/// from the end. Offsets beyond either end of the input are out of range.
+pub fn resolve_offset(offset: i64, size: u64) -> Result<u64> {
+    let position = if offset >= 0 {
+        Some(offset as u64).filter(|&position| position <= size)
+    } else {
+        size.checked_sub(offset.unsigned_abs())
+    };
+    position.ok_or(Error::OffsetOutOfRange { offset, size })
+}
+
 /// Returns the appropriate buffered reader and the starting offset
 pub fn get_reader_and_offset(
     filepath: Option<&std::path::PathBuf>,
     offset: i64,
-) -> Result<(Box<dyn std::io::BufRead>, usize), Box<dyn std::error::Error>> {
+) -> Result<(Box<dyn std::io::BufRead>, usize)> {
     match filepath {
         // If a `filepath` was passed in the arguments, read the file ...
         Some(filepath) => get_file_reader(filepath, offset),
         // otherwise, read the input from stdin.
-        None => get_stdin_reader(),
+        None => get_stream_reader(std::io::stdin(), offset),
+    }
+}
+
+/// Returns a buffered reader to read from a stream (e.g. STDIN or a pipe) and the starting offset.
+///
+/// As streams cannot seek, a positive offset discards that many bytes from the start of the stream,
+/// while a negative offset reads the stream to the end and keeps only that many of the last bytes.
+pub fn get_stream_reader<T>(stream: T, offset: i64) -> Result<(Box<dyn std::io::BufRead>, usize)>
+where
+    T: std::io::Read + 'static,
+{
+    let mut data = std::io::BufReader::new(stream);
+    if offset >= 0 {
+        let skipped = skip(&mut data, offset as u64)?;
+        if skipped < offset as usize {
+            // The stream ended before the offset was reached
+            resolve_offset(offset, skipped as u64)?;
+        }
+        Ok((Box::new(data), skipped))
+    } else {
+        let (tail, start) = read_tail(data, offset.unsigned_abs() as usize)?;
+        resolve_offset(offset, (start + tail.len()) as u64)?;
+        Ok((Box::new(std::io::Cursor::new(tail)), start))
     }
 }
 
-/// Returns a buffered reader to read from STDIN and the starting offset (Always 0 for STDIN)
-fn get_stdin_reader() -> Result<(Box<dyn std::io::BufRead>, usize), Box<dyn std::error::Error>> {
-    let offset = 0; // Offset is not supported in this mode
-    let data = std::io::stdin();
-    Ok((Box::new(std::io::BufReader::new(data)), offset))
+/// Discards up to `n` bytes from the reader and returns the number of bytes discarded
+fn skip<T>(reader: &mut T, n: u64) -> std::io::Result<usize>
+where
+    T: std::io::Read,
+{
+    let skipped = std::io::copy(&mut reader.take(n), &mut std::io::sink())?;
+    Ok(skipped as usize)
 }
 
-/// Opens a file at the specified `filepath` and returns a buffered reader along with the starting offset
+/// Reads the reader to the end, keeping only the last `n` bytes in a ring buffer.
+///
+/// Returns the last bytes along with their offset from the start of the stream.
+fn read_tail<T>(mut reader: T, n: usize) -> std::io::Result<(Vec<u8>, usize)>
+where
+    T: std::io::Read,
+{
+    // The tail grows as the bytes are read, as `n` may be far larger than the stream
+    let mut buffer = vec![0; 64 * 1024];
+    let mut tail = std::collections::VecDeque::with_capacity(std::cmp::min(n, buffer.len()));
+    let mut total = 0;
+    loop {
+        let bytes_read = read_chunk(&mut reader, &mut buffer)?;
+        if bytes_read == 0 {
+            break;
+        }
+        total += bytes_read;
+
+        // Only the last `n` bytes of the chunk can make it into the tail
+        let chunk = &buffer[bytes_read.saturating_sub(n)..bytes_read];
+        let excess = (tail.len() + chunk.len()).saturating_sub(n);
+        tail.drain(..excess);
+        tail.extend(chunk);
+    }
+    let start = total - tail.len();
+    Ok((tail.into(), start))
+}
+
+/// Opens a file at the specified `filepath` and returns a buffered reader along with the starting offset.
+///
+/// Regular files are memory-mapped, while pipes and special files are read through like STDIN.
 fn get_file_reader(
     filepath: &std::path::PathBuf,
-    mut offset: i64,
-) -> Result<(Box<dyn std::io::BufRead>, usize), Box<dyn std::error::Error>> {
+    offset: i64,
+) -> Result<(Box<dyn std::io::BufRead>, usize)> {
     let mut file = std::fs::File::open(filepath)?;
+    if !file.metadata()?.is_file() {
+        return get_stream_reader(file, offset);
+    }
 
-    // A positive offset seeks forwards from the start of the file
-    if offset >= 0 {
-        file.seek(std::io::SeekFrom::Start(offset as u64))?;
-    } else if offset < 0 {
-        // ... while an negative offset seeks backwards from the end of the file
-        let file_size = file.seek(std::io::SeekFrom::End(0))?;
-        file.seek(std::io::SeekFrom::End(offset))?;
-        offset = file_size as i64 + offset;
+    let file_size = file.metadata()?.len();
+    let start = resolve_offset(offset, file_size)?;
+
+    // Regular files are read straight from a memory-map of the rest of the file
+    let len = (file_size - start) as usize;
+    if let Some(mapped) = Mapped::new(&file, start as usize, len) {
+        return Ok((Box::new(mapped), start as usize));
     }
 
-    Ok((Box::new(std::io::BufReader::new(file)), offset as usize))
+    file.seek(std::io::SeekFrom::Start(start))?;
+    Ok((Box::new(std::io::BufReader::new(file)), start as usize))
+}
+
+/// A reader that can also seek to arbitrary positions
+pub trait ReadSeek: std::io::Read + std::io::Seek {}
+impl<T: std::io::Read + std::io::Seek> ReadSeek for T {}
+
+/// An in-memory copy of a part of a stream, which seeks to the same positions as the
+/// original stream would (i.e. the first byte is at the `start` position)
+pub struct OffsetCursor {
+    data: std::io::Cursor<Vec<u8>>, // The bytes from the stream
+    start: u64,                     // The position of the first byte in the stream
+}
+
+impl OffsetCursor {
+    pub fn new(data: Vec<u8>, start: u64) -> Self {
+        Self {
+            data: std::io::Cursor::new(data),
+            start,
+        }
+    }
+}
+
+impl std::io::Read for OffsetCursor {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.data.read(buf)
+    }
+}
+
+impl std::io::Seek for OffsetCursor {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        let pos = match pos {
+            std::io::SeekFrom::Start(n) => {
+                let n = n.checked_sub(self.start).ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        "cannot seek to before the start of the data",
+                    )
+                })?;
+                std::io::SeekFrom::Start(n)
+            }
+            pos => pos,
+        };
+        Ok(self.data.seek(pos)? + self.start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_classify_printable_ascii_characters() {
+        assert!(is_printable_ascii_character(&b'a'));
+        assert!(is_printable_ascii_character(&b' '));
+        assert!(is_printable_ascii_character(&b'~'));
+        assert!(!is_printable_ascii_character(&0x1b));
+        assert!(!is_printable_ascii_character(&b'\n'));
+        assert!(!is_printable_ascii_character(&0x7f));
+        assert!(!is_printable_ascii_character(&0x9b));
+    }
+
+    #[test]
+    fn should_resolve_offsets_within_range() {
+        assert_eq!(resolve_offset(4, 10).unwrap(), 4);
+        assert_eq!(resolve_offset(10, 10).unwrap(), 10);
+        assert_eq!(resolve_offset(-4, 10).unwrap(), 6);
+        assert!(matches!(
+            resolve_offset(11, 10),
+            Err(Error::OffsetOutOfRange {
+                offset: 11,
+                size: 10
+            })
+        ));
+        assert!(resolve_offset(-11, 10).is_err());
+    }
+
+    #[test]
+    fn should_skip_bytes_of_a_stream() {
+        let mut data: &[u8] = b"abcdef";
+        assert_eq!(skip(&mut data, 4).unwrap(), 4);
+        assert_eq!(data, b"ef");
+        assert_eq!(skip(&mut data, 4).unwrap(), 2);
+    }
+
+    #[test]
+    fn should_keep_the_tail_of_a_stream() {
+        let data: Vec<u8> = (0..200_000u32).map(|i| i as u8).collect();
+        let (tail, start) = read_tail(data.as_slice(), 100_000).unwrap();
+        assert_eq!(start, 100_000);
+        assert_eq!(tail, data[100_000..]);
+
+        let (tail, start) = read_tail(&b"abc"[..], 8).unwrap();
+        assert_eq!((tail.as_slice(), start), (&b"abc"[..], 0));
+
+        // Only the bytes that are read are held, however many are asked for
+        let (tail, start) = read_tail(&b"abc"[..], usize::MAX).unwrap();
+        assert_eq!((tail.as_slice(), start), (&b"abc"[..], 0));
+    }
+
+    #[test]
+    fn should_seek_to_the_positions_in_the_stream() {
+        let mut cursor = OffsetCursor::new(b"abcdef".to_vec(), 100);
+        assert_eq!(cursor.seek(std::io::SeekFrom::Start(102)).unwrap(), 102);
+        let mut buffer = [0; 2];
+        cursor.read_exact(&mut buffer).unwrap();
+        assert_eq!(&buffer, b"cd");
+        assert!(cursor.seek(std::io::SeekFrom::Start(99)).is_err());
+    }
 }
@@ -3,5 +3,8 @@
 // ---------
 
 pub mod ansi;
+pub mod encoding;
 pub mod format;
 pub mod helpers;
+pub mod source;
+pub mod theme;
@@ -0,0 +1,258 @@
+// Library
+use std::borrow::Cow;
+use std::io::{Read, SeekFrom};
+use std::ops::Range;
+
+use super::helpers::{self, OffsetCursor, ReadSeek};
+use crate::error::Result;
+
+// ------
+// SOURCE
+// ------
+
+/// Random access to a span of bytes, regardless of where they come from.
+///
+/// Regular files are memory-mapped so that their bytes can be borrowed directly, without
+/// copying them. Everything else (pipes, special files and STDIN) is seeked into and read.
+pub trait Source {
+    /// The position in the file (or stream) at which the data begins
+    fn start(&self) -> usize;
+
+    /// The total number of bytes available
+    fn len(&self) -> usize;
+
+    /// Returns true if there are no bytes available
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the bytes in the given range of indices (relative to the start of the data).
+    ///
+    /// The range is clamped to the end of the data, so fewer bytes are returned near the end.
+    fn bytes(&mut self, range: Range<usize>) -> std::io::Result<Cow<'_, [u8]>>;
+}
+
+/// Opens the source for the file at `filepath` (or STDIN, if empty) beginning at the `offset`
+/// and spanning up to `limit` bytes.
+///
+/// A positive offset counts forwards from the start of the file, while a negative offset counts
+/// backwards from the end of the file. As STDIN, pipes and special files cannot seek, their
+/// contents are read into memory in full instead (up to the `limit`).
+pub fn open(
+    filepath: Option<&std::path::PathBuf>,
+    offset: i64,
+    limit: Option<usize>,
+) -> Result<Box<dyn Source>> {
+    let file = filepath.map(std::fs::File::open).transpose()?;
+    match file {
+        Some(file) if file.metadata()?.is_file() => {
+            let file_size = file.metadata()?.len();
+            let start = helpers::resolve_offset(offset, file_size)? as usize;
+
+            let size = file_size as usize - start;
+            let size = limit.map_or(size, |limit| std::cmp::min(limit, size));
+            match Mapped::new(&file, start, size) {
+                Some(mapped) => Ok(Box::new(mapped)),
+                None => Ok(Box::new(Buffered::new(Box::new(file), start, size))),
+            }
+        }
+        file => {
+            let (mut stream, start) = match file {
+                Some(file) => helpers::get_stream_reader(file, offset)?,
+                None => helpers::get_stream_reader(std::io::stdin(), offset)?,
+            };
+            let mut data = Vec::new();
+            match limit {
+                Some(limit) => stream.take(limit as u64).read_to_end(&mut data)?,
+                None => stream.read_to_end(&mut data)?,
+            };
+            let size = data.len();
+            let cursor = OffsetCursor::new(data, start as u64);
+            Ok(Box::new(Buffered::new(Box::new(cursor), start, size)))
+        }
+    }
+}
+
+// ------
+// MAPPED
+// ------
+
+/// A memory-mapped span of a regular file.
+///
+/// Besides random access, it can also be read through as a stream, straight from the map.
+pub struct Mapped {
+    map: memmap2::Mmap, // The mapped bytes of the file
+    start: usize,       // The position in the file at which the map begins
+    position: usize,    // The index of the next byte to read, when read as a stream
+}
+
+impl Mapped {
+    /// Memory-map `len` bytes of the file beginning at the `start` position.
+    ///
+    /// Returns `None` if the file cannot be mapped (e.g. it is a pipe, a special file or empty),
+    /// in which case it has to be read instead.
+    pub fn new(file: &std::fs::File, start: usize, len: usize) -> Option<Self> {
+        if len == 0 || !file.metadata().ok()?.is_file() {
+            return None;
+        }
+        // SAFETY: The map is only ever read from. Another process could still truncate or modify
+        // the file while it is mapped, but the same holds for any tool that maps its input, and
+        // it spares us from copying multi-gigabyte files into memory.
+        let map = unsafe {
+            memmap2::MmapOptions::new()
+                .offset(start as u64)
+                .len(len)
+                .map(file)
+        };
+        Some(Self {
+            map: map.ok()?,
+            start,
+            position: 0,
+        })
+    }
+}
+
+impl Source for Mapped {
+    fn start(&self) -> usize {
+        self.start
+    }
+
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    fn bytes(&mut self, range: Range<usize>) -> std::io::Result<Cow<'_, [u8]>> {
+        let (start, end) = clamp(range, self.map.len());
+        Ok(Cow::Borrowed(&self.map[start..end]))
+    }
+}
+
+impl std::io::Read for Mapped {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = (&self.map[self.position..]).read(buf)?;
+        self.position += n;
+        Ok(n)
+    }
+}
+
+impl std::io::BufRead for Mapped {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        Ok(&self.map[self.position..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.position = std::cmp::min(self.position + amt, self.map.len());
+    }
+}
+
+// --------
+// BUFFERED
+// --------
+
+/// A span of a seekable reader, whose bytes are read into a new buffer whenever they are accessed
+pub struct Buffered {
+    reader: Box<dyn ReadSeek>, // The seekable reader
+    start: usize,              // The position in the reader at which the data begins
+    len: usize,                // The total number of bytes available
+}
+
+impl Buffered {
+    /// Instantiate a new source for `len` bytes of the `reader`, beginning at the `start` position
+    pub fn new(reader: Box<dyn ReadSeek>, start: usize, len: usize) -> Self {
+        Self { reader, start, len }
+    }
+}
+
+impl From<Vec<u8>> for Buffered {
+    /// A source for bytes that are already in memory
+    fn from(data: Vec<u8>) -> Self {
+        let len = data.len();
+        Self::new(Box::new(std::io::Cursor::new(data)), 0, len)
+    }
+}
+
+impl Source for Buffered {
+    fn start(&self) -> usize {
+        self.start
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn bytes(&mut self, range: Range<usize>) -> std::io::Result<Cow<'_, [u8]>> {
+        let (start, end) = clamp(range, self.len);
+        let mut buffer = vec![0; end - start];
+        self.reader
+            .seek(SeekFrom::Start((self.start + start) as u64))?;
+        let n = helpers::read_chunk(&mut self.reader, &mut buffer)?;
+        buffer.truncate(n);
+        Ok(Cow::Owned(buffer))
+    }
+}
+
+/// Clamp the range to the given length, returning its (ordered) bounds
+fn clamp(range: Range<usize>, len: usize) -> (usize, usize) {
+    let end = std::cmp::min(range.end, len);
+    (std::cmp::min(range.start, end), end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, Write};
+
+    /// Write the bytes to a new file in the temporary directory
+    fn temp_file(name: &str, data: &[u8]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("hex-ray-{}-{}", std::process::id(), name));
+        std::fs::File::create(&path)
+            .and_then(|mut file| file.write_all(data))
+            .unwrap();
+        path
+    }
+
+    #[test]
+    fn should_map_regular_files() {
+        let data: Vec<u8> = (0..=255).collect();
+        let path = temp_file("mapped", &data);
+
+        let mut source = open(Some(&path), -56, Some(32)).unwrap();
+        assert_eq!((source.start(), source.len()), (200, 32));
+        assert!(matches!(
+            source.bytes(0..4).unwrap(),
+            Cow::Borrowed(&[200, 201, 202, 203])
+        ));
+        assert_eq!(*source.bytes(30..40).unwrap(), [230, 231]);
+
+        let file = std::fs::File::open(&path).unwrap();
+        let mut mapped = Mapped::new(&file, 250, 6).unwrap();
+        mapped.consume(2);
+        let mut rest = Vec::new();
+        mapped.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, [252, 253, 254, 255]);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn should_fall_back_to_reading_empty_files() {
+        let path = temp_file("empty", &[]);
+        let file = std::fs::File::open(&path).unwrap();
+        assert!(Mapped::new(&file, 0, 0).is_none());
+
+        let mut source = open(Some(&path), 0, None).unwrap();
+        assert!(source.is_empty());
+        assert!(source.bytes(0..16).unwrap().is_empty());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn should_read_buffered_sources() {
+        let cursor = std::io::Cursor::new(b"abcdefgh".to_vec());
+        let mut source = Buffered::new(Box::new(cursor), 2, 4);
+        assert_eq!(*source.bytes(1..3).unwrap(), *b"de");
+        assert_eq!(*source.bytes(2..10).unwrap(), *b"ef");
+        assert!(source.bytes(8..10).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,197 @@
+// Library
+use super::ansi::Color;
+
+// ----------
+// BYTE CLASS
+// ----------
+
+/// The categories of bytes that are colored differently, to tell the kinds of data apart at a glance
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteClass {
+    /// The NUL byte (`0x00`)
+    Null,
+    /// A printable ASCII character (`!` to `~`)
+    Printable,
+    /// An ASCII whitespace character (space, tab, line feed, vertical tab, form feed or carriage return)
+    Whitespace,
+    /// Any other ASCII control character (including DEL)
+    Control,
+    /// A byte with the high bit set, other than `0xFF`
+    High,
+    /// The `0xFF` byte (e.g. the padding of erased flash)
+    Ff,
+}
+
+impl ByteClass {
+    /// Classify the byte
+    pub fn of(byte: u8) -> Self {
+        match byte {
+            0x00 => ByteClass::Null,
+            b' ' | b'\t' | b'\n' | 0x0b | 0x0c | b'\r' => ByteClass::Whitespace,
+            0x21..=0x7e => ByteClass::Printable,
+            0x01..=0x7f => ByteClass::Control,
+            0xff => ByteClass::Ff,
+            _ => ByteClass::High,
+        }
+    }
+
+    /// Classify a multi-byte value by its first non-zero byte (or as NUL if all of them are zero)
+    pub fn of_bytes(bytes: &[u8]) -> Self {
+        bytes
+            .iter()
+            .find(|&&b| b != 0)
+            .map_or(ByteClass::Null, |&b| ByteClass::of(b))
+    }
+}
+
+// -------
+// PALETTE
+// -------
+
+/// The colors of each class of bytes, along with the colors that highlight the bytes in `inspect`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Palette {
+    pub null: Color,       // The color of NUL bytes
+    pub printable: Color,  // The color of printable ASCII characters
+    pub whitespace: Color, // The color of ASCII whitespace
+    pub control: Color,    // The color of the other ASCII control characters
+    pub high: Color,       // The color of the bytes with the high bit set
+    pub ff: Color,         // The color of `0xFF` bytes
+    pub selected: Color,   // The background of the selected byte
+    pub matched: Color,    // The background of the bytes that match the search
+    pub range: Color,      // The background of the bytes in the selected range
+    pub modified: Color,   // The color of the bytes that have been modified
+    pub different: Color,  // The color of the bytes that differ from the file compared against
+}
+
+impl Palette {
+    /// The color of the given class of bytes
+    pub fn color(&self, class: ByteClass) -> Color {
+        match class {
+            ByteClass::Null => self.null,
+            ByteClass::Printable => self.printable,
+            ByteClass::Whitespace => self.whitespace,
+            ByteClass::Control => self.control,
+            ByteClass::High => self.high,
+            ByteClass::Ff => self.ff,
+        }
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Theme::default().palette()
+    }
+}
+
+// -----
+// THEME
+// -----
+
+/// The built-in color themes
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Theme {
+    /// The 16 standard terminal colors, so that the terminal's own color scheme applies
+    #[default]
+    Default,
+    /// Only dims the NUL bytes
+    Mono,
+    /// Soft colors from the 256-color palette
+    Pastel,
+    /// The Gruvbox colors (24-bit)
+    Gruvbox,
+    /// The Solarized colors (24-bit)
+    Solarized,
+}
+
+impl Theme {
+    /// The colors of this theme
+    pub fn palette(self) -> Palette {
+        match self {
+            Theme::Default => Palette {
+                null: Color::BrightBlack,
+                printable: Color::Cyan,
+                whitespace: Color::Green,
+                control: Color::Magenta,
+                high: Color::Yellow,
+                ff: Color::Red,
+                selected: Color::Rgb(255, 146, 92),
+                matched: Color::Rgb(86, 156, 214),
+                range: Color::Rgb(128, 73, 46),
+                modified: Color::BrightRed,
+                different: Color::Yellow,
+            },
+            Theme::Mono => Palette {
+                null: Color::BrightBlack,
+                printable: Color::White,
+                whitespace: Color::White,
+                control: Color::White,
+                high: Color::White,
+                ff: Color::White,
+                selected: Color::Rgb(255, 146, 92),
+                matched: Color::Rgb(86, 156, 214),
+                range: Color::Rgb(128, 73, 46),
+                modified: Color::BrightRed,
+                different: Color::Yellow,
+            },
+            Theme::Pastel => Palette {
+                null: Color::Fixed(240),
+                printable: Color::Fixed(117),
+                whitespace: Color::Fixed(150),
+                control: Color::Fixed(183),
+                high: Color::Fixed(222),
+                ff: Color::Fixed(210),
+                selected: Color::Fixed(216),
+                matched: Color::Fixed(110),
+                range: Color::Fixed(95),
+                modified: Color::Fixed(210),
+                different: Color::Fixed(229),
+            },
+            Theme::Gruvbox => Palette {
+                null: Color::Rgb(0x66, 0x5c, 0x54),
+                printable: Color::Rgb(0x8e, 0xc0, 0x7c),
+                whitespace: Color::Rgb(0xb8, 0xbb, 0x26),
+                control: Color::Rgb(0xd3, 0x86, 0x9b),
+                high: Color::Rgb(0xfa, 0xbd, 0x2f),
+                ff: Color::Rgb(0xfb, 0x49, 0x34),
+                selected: Color::Rgb(0xfe, 0x80, 0x19),
+                matched: Color::Rgb(0x83, 0xa5, 0x98),
+                range: Color::Rgb(0x50, 0x49, 0x45),
+                modified: Color::Rgb(0xfb, 0x49, 0x34),
+                different: Color::Rgb(0xfa, 0xbd, 0x2f),
+            },
+            Theme::Solarized => Palette {
+                null: Color::Rgb(0x58, 0x6e, 0x75),
+                printable: Color::Rgb(0x2a, 0xa1, 0x98),
+                whitespace: Color::Rgb(0x85, 0x99, 0x00),
+                control: Color::Rgb(0xd3, 0x36, 0x82),
+                high: Color::Rgb(0xb5, 0x89, 0x00),
+                ff: Color::Rgb(0xdc, 0x32, 0x2f),
+                selected: Color::Rgb(0xcb, 0x4b, 0x16),
+                matched: Color::Rgb(0x26, 0x8b, 0xd2),
+                range: Color::Rgb(0x07, 0x36, 0x42),
+                modified: Color::Rgb(0xdc, 0x32, 0x2f),
+                different: Color::Rgb(0xb5, 0x89, 0x00),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_classify_bytes() {
+        assert_eq!(ByteClass::of(0x00), ByteClass::Null);
+        assert_eq!(ByteClass::of(b'A'), ByteClass::Printable);
+        assert_eq!(ByteClass::of(b' '), ByteClass::Whitespace);
+        assert_eq!(ByteClass::of(b'\r'), ByteClass::Whitespace);
+        assert_eq!(ByteClass::of(0x1b), ByteClass::Control);
+        assert_eq!(ByteClass::of(0x7f), ByteClass::Control);
+        assert_eq!(ByteClass::of(0x80), ByteClass::High);
+        assert_eq!(ByteClass::of(0xff), ByteClass::Ff);
+        assert_eq!(ByteClass::of_bytes(&[0, 0, 0xff]), ByteClass::Ff);
+        assert_eq!(ByteClass::of_bytes(&[0, 0]), ByteClass::Null);
+    }
+}